@@ -0,0 +1,116 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use graph_depth_search::topology::*;
+
+// Spine of `len` nodes where every spine node also has a dead-end stub:
+//
+//   0(2) -- (1)1(2) -- (1)2 ... (1)len-1
+//   (3)       (3)        (3)
+//    |         |          |
+//   stub      stub       stub
+//
+// There is a single route between the spine ends, so the number of paths
+// stays at one while the path (and the DFS depth) grows with `len`. Any
+// cycle would make the number of simple paths explode instead.
+fn create_comb(len: u32) -> Topology {
+    let stub_id = |i: u32| NodeId(len + i);
+    let mut topo = Topology::new();
+
+    for i in 0..len {
+        let mut node = TopologyNode::new(NodeId(i));
+        node.add_iface(Interface::new(IfaceIndex(0), InterfaceType::LocalApp, vec![]));
+        if i > 0 {
+            node.add_iface(Interface::new(IfaceIndex(1), InterfaceType::LocalNet, vec![(NodeId(i - 1), IfaceIndex(2))]));
+        }
+        if i + 1 < len {
+            node.add_iface(Interface::new(IfaceIndex(2), InterfaceType::LocalNet, vec![(NodeId(i + 1), IfaceIndex(1))]));
+        }
+        node.add_iface(Interface::new(IfaceIndex(3), InterfaceType::LocalNet, vec![(stub_id(i), IfaceIndex(1))]));
+        topo.add_node(node);
+
+        let mut stub = TopologyNode::new(stub_id(i));
+        stub.add_iface(Interface::new(IfaceIndex(0), InterfaceType::LocalApp, vec![]));
+        stub.add_iface(Interface::new(IfaceIndex(1), InterfaceType::LocalNet, vec![(NodeId(i), IfaceIndex(3))]));
+        topo.add_node(stub);
+    }
+    topo
+}
+
+// `side` x `side` grid, node `row * side + col`, linked to its neighbors
+// above (1), below (2), left (3) and right (4):
+//
+//   0(4) -- (3)1(4) -- (3)2
+//   (2)       (2)        (2)
+//    |         |          |
+//   (1)       (1)        (1)
+//   3(4) -- (3)4(4) -- (3)5
+//
+// Corner to corner the number of simple paths grows very fast (12 for 3x3,
+// 184 for 4x4, 8512 for 5x5), so unlike the comb this is dominated by
+// branching and revisit checks rather than by path length.
+fn create_grid(side: u32) -> Topology {
+    let id = |row: u32, col: u32| NodeId(row * side + col);
+    let mut topo = Topology::new();
+
+    for row in 0..side {
+        for col in 0..side {
+            let mut node = TopologyNode::new(id(row, col));
+            node.add_iface(Interface::new(IfaceIndex(0), InterfaceType::LocalApp, vec![]));
+            if row > 0 {
+                node.add_iface(Interface::new(IfaceIndex(1), InterfaceType::LocalNet, vec![(id(row - 1, col), IfaceIndex(2))]));
+            }
+            if row + 1 < side {
+                node.add_iface(Interface::new(IfaceIndex(2), InterfaceType::LocalNet, vec![(id(row + 1, col), IfaceIndex(1))]));
+            }
+            if col > 0 {
+                node.add_iface(Interface::new(IfaceIndex(3), InterfaceType::LocalNet, vec![(id(row, col - 1), IfaceIndex(4))]));
+            }
+            if col + 1 < side {
+                node.add_iface(Interface::new(IfaceIndex(4), InterfaceType::LocalNet, vec![(id(row, col + 1), IfaceIndex(3))]));
+            }
+            topo.add_node(node);
+        }
+    }
+    topo
+}
+
+fn find_path_grid(c: &mut Criterion) {
+    let mut group = c.benchmark_group("find_path_grid");
+
+    for side in [4, 5] {
+        let topo = create_grid(side);
+        let finish_id = NodeId(side * side - 1);
+
+        group.bench_with_input(BenchmarkId::from_parameter(side), &topo, |b, topo| {
+            b.iter(|| {
+                let mut path = Path::new();
+                let mut paths: Vec<Path> = Vec::new();
+                topo.find_path(NodeId(0), IfaceIndex(0), finish_id, IfaceIndex(0), &mut path, &mut paths);
+                paths
+            })
+        });
+    }
+    group.finish();
+}
+
+fn find_path_comb(c: &mut Criterion) {
+    let mut group = c.benchmark_group("find_path_comb");
+
+    for len in [256, 1024, 4096] {
+        let topo = create_comb(len);
+        let start_id = NodeId(0);
+        let finish_id = NodeId(len - 1);
+
+        group.bench_with_input(BenchmarkId::from_parameter(len), &topo, |b, topo| {
+            b.iter(|| {
+                let mut path = Path::new();
+                let mut paths: Vec<Path> = Vec::new();
+                topo.find_path(start_id, IfaceIndex(0), finish_id, IfaceIndex(0), &mut path, &mut paths);
+                paths
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, find_path_comb, find_path_grid);
+criterion_main!(benches);
@@ -1 +1,14 @@
-mod topology;
+//! Path search over network topologies.
+//!
+//! With the default `std` feature off the crate only needs `alloc`. The
+//! `HashMap` and `HashSet` in its API are then `BTreeMap` and `BTreeSet`.
+//!
+//! The `serde` feature implements `Serialize` for `Topology`, with nodes,
+//! interfaces and neighbors sorted so the output is stable. Labels are not
+//! serialized, as they don't count towards equality. `yaml` adds
+//! `Topology::from_yaml` and `Topology::to_yaml`.
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
+
+pub mod topology;
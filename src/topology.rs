@@ -1,9 +1,64 @@
-use std::collections::HashMap;
-use std::collections::VecDeque;
-use std::fmt::Display;
-
-#[derive(Debug, Clone)]
-struct PathNode {
+use alloc::collections::vec_deque;
+use alloc::format;
+use alloc::string::String;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use alloc::vec;
+use core::cmp::Reverse;
+use core::fmt::Display;
+use core::ops::ControlFlow;
+
+use collections::BinaryHeap;
+use collections::HashMap;
+use collections::HashSet;
+use collections::VecDeque;
+
+mod best_first;
+mod builder;
+mod centrality;
+mod collections;
+mod edit;
+mod error;
+mod export;
+pub mod fixtures;
+mod flow;
+mod gateways;
+mod metrics;
+mod policy;
+mod routing;
+mod sample;
+#[cfg(feature = "serde")]
+mod serialize;
+mod stats;
+mod traffic;
+mod tracker;
+mod validate;
+mod visitor;
+#[cfg(feature = "yaml")]
+mod yaml;
+
+pub use builder::InterfaceBuilder;
+pub use builder::TopologyBuilder;
+pub use error::PathError;
+pub use error::TopologyError;
+pub use metrics::ComponentInfo;
+pub use policy::TransitPolicy;
+pub use routing::Route;
+pub use stats::TopologyStats;
+pub use tracker::ConnectivityTracker;
+pub use visitor::PathVisitor;
+pub use visitor::SearchStats;
+pub use visitor::StepContext;
+#[cfg(feature = "yaml")]
+pub use yaml::YamlError;
+
+use visitor::DetailedPathCollector;
+use visitor::PathCollector;
+
+/// One node of a `Path`, with the interfaces the path enters and leaves it
+/// through.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathNode {
     id: NodeId,
     forward_if_id: IfaceIndex,
     reverse_if_id: IfaceIndex,
@@ -17,29 +72,116 @@ impl PathNode {
             reverse_if_id: Default::default(),
         }
     }
+
+    pub fn id(&self) -> NodeId {
+        self.id
+    }
+
+    /// Interface the path leaves the node through; on the last node, the
+    /// finish interface.
+    pub fn forward(&self) -> IfaceIndex {
+        self.forward_if_id
+    }
+
+    /// Interface the path enters the node through; on the first node, the
+    /// start interface.
+    pub fn reverse(&self) -> IfaceIndex {
+        self.reverse_if_id
+    }
 }
 
 impl Display for PathNode {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         f.write_fmt(format_args!("({}){}({})", self.reverse_if_id, self.id, self.forward_if_id))
     }
 }
 
-#[derive(Clone)]
-struct Path {
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Path {
     nodes: VecDeque<PathNode>,
 }
 
 impl Path {
-    fn new() -> Self {
+    pub fn new() -> Self {
         Path {
             nodes: VecDeque::new()
         }
     }
+
+    /// The nodes of the path from start to finish.
+    pub fn iter(&self) -> vec_deque::Iter<'_, PathNode> {
+        self.nodes.iter()
+    }
+
+    /// Sum of the `cost` of the interfaces every hop leaves through. The
+    /// finish interface of the last node isn't a hop and doesn't count. The
+    /// sum saturates at `u32::MAX` instead of wrapping.
+    ///
+    /// Panics if the path goes through nodes or interfaces missing in `topo`.
+    pub fn total_cost(&self, topo: &Topology) -> u32 {
+        self.nodes
+            .iter()
+            .take(self.nodes.len().saturating_sub(1))
+            .fold(0, |cost: u32, x| cost.saturating_add(topo.link_cost(x.id, x.forward_if_id)))
+    }
+
+    /// Like the `Display` output, but a path of more than `max_nodes` nodes
+    /// only shows its first and last nodes, `max_nodes` in all, with `...`
+    /// standing in for the rest.
+    pub fn display_truncated(&self, max_nodes: usize) -> String {
+        if self.nodes.len() <= max_nodes {
+            return self.to_string();
+        }
+
+        let tail = max_nodes / 2;
+        let head = max_nodes - tail;
+        let shown: Vec<String> = self.nodes.iter().take(head).map(|x| x.to_string())
+                                     .chain([String::from("...")])
+                                     .chain(self.nodes.iter().skip(self.nodes.len() - tail).map(|x| x.to_string()))
+                                     .collect();
+        format!("Path: {}", shown.join(" => "))
+    }
+
+    /// One line per hop, e.g. `A exits via iface 2 -> B enters via iface 1`.
+    pub fn describe(&self) -> String {
+        self.nodes
+            .iter()
+            .zip(self.nodes.iter().skip(1))
+            .map(|(from, to)| format!("{} exits via iface {} -> {} enters via iface {}",
+                                      from.id, from.forward_if_id, to.id, to.reverse_if_id))
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+impl<'a> IntoIterator for &'a Path {
+    type Item = &'a PathNode;
+    type IntoIter = vec_deque::Iter<'a, PathNode>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl Default for Path {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A path found by `find_paths_detailed`, with the metrics callers usually
+/// rank routes by.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathResult {
+    pub path: Path,
+    /// Links crossed, one less than the number of nodes.
+    pub hops: usize,
+    /// `Path::total_cost` of `path`.
+    pub cost: u32,
 }
 
 impl Display for Path {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         f.write_fmt(format_args!("Path: "))?;
         f.write_fmt(format_args!("{}",
                                  self.nodes
@@ -51,110 +193,545 @@ impl Display for Path {
     }
 }
 
-#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
-pub(crate) struct NodeId(u32);
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, PartialOrd, Ord)]
+pub struct NodeId(pub u32);
+
+// Predecessor of each node in a shortest-path tree:
+// (previous node, its exit iface, entry iface).
+type Predecessors = HashMap<NodeId, (NodeId, IfaceIndex, IfaceIndex)>;
 
 impl Display for NodeId {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         f.write_fmt(format_args!("{:X}", self.0))
     }
 }
 
-#[derive(Debug, PartialEq)]
-enum InterfaceType {
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum InterfaceType {
     LocalApp,
     LocalNet,
     Internet,
+    /// Attachment to a multi-access segment such as an Ethernet LAN, listing
+    /// every other member of the segment. Carries traffic like `LocalNet`.
+    SharedMedium,
 }
 
-#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]
-pub(crate) struct IfaceIndex(u8);
+impl InterfaceType {
+    /// Whether interfaces of this type link nodes to each other.
+    pub fn is_link(self) -> bool {
+        matches!(self, InterfaceType::LocalNet | InterfaceType::SharedMedium)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Default, PartialOrd, Ord)]
+pub struct IfaceIndex(pub u8);
 
 impl Display for IfaceIndex {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         f.write_fmt(format_args!("{}", self.0))
     }
 }
 
-#[derive(Debug)]
-struct Interface {
+// Labels are metadata for people and external systems: they are ignored by
+// pathfinding and by `==`.
+#[derive(Debug, Clone)]
+pub struct Interface {
     id: IfaceIndex,
     if_type: InterfaceType,
     neighbors: Vec<(NodeId, IfaceIndex)>,
+    cost: u32,
+    explicit_cost: bool,
+    bandwidth: u32,
+    admin_up: bool,
+    oper_up: bool,
+    label: Option<String>,
+}
+
+impl PartialEq for Interface {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+            && self.if_type == other.if_type
+            && self.neighbors == other.neighbors
+            && self.cost == other.cost
+            && self.explicit_cost == other.explicit_cost
+            && self.bandwidth == other.bandwidth
+            && self.admin_up == other.admin_up
+            && self.oper_up == other.oper_up
+    }
+}
+
+impl Eq for Interface {}
+
+impl Display for Interface {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match &self.label {
+            Some(label) => f.write_fmt(format_args!("{} ({})", self.id, label)),
+            None => f.write_fmt(format_args!("{}", self.id)),
+        }
+    }
 }
 
 impl Interface {
-    fn new(id: IfaceIndex,
-           if_type: InterfaceType,
-           neighbors: Vec<(NodeId, IfaceIndex)>
+    pub fn new(id: IfaceIndex,
+               if_type: InterfaceType,
+               neighbors: Vec<(NodeId, IfaceIndex)>
     ) -> Self {
         Self {
             id,
             if_type,
             neighbors,
+            cost: 1,
+            explicit_cost: false,
+            bandwidth: u32::MAX,
+            admin_up: true,
+            oper_up: true,
+            label: None,
+        }
+    }
+
+    pub fn index(&self) -> IfaceIndex {
+        self.id
+    }
+
+    pub fn iface_type(&self) -> InterfaceType {
+        self.if_type
+    }
+
+    pub fn neighbors(&self) -> &[(NodeId, IfaceIndex)] {
+        &self.neighbors
+    }
+
+    /// Appends a neighbor unless it is listed already.
+    pub fn add_neighbor(&mut self, node: NodeId, iface: IfaceIndex) {
+        if !self.neighbors.contains(&(node, iface)) {
+            self.neighbors.push((node, iface));
         }
     }
+
+    /// Cost of leaving the node through this interface, 1 by default. Costs
+    /// summed along a path saturate at `u32::MAX` rather than wrapping, so
+    /// any path that reaches it counts as maximally expensive.
+    pub fn cost(&self) -> u32 {
+        self.cost
+    }
+
+    /// Sets the cost explicitly, which `Topology::set_type_costs` won't
+    /// override afterwards.
+    pub fn set_cost(&mut self, cost: u32) {
+        self.cost = cost;
+        self.explicit_cost = true;
+    }
+
+    /// Capacity of the interface, `u32::MAX` (unconstrained) by default.
+    pub fn bandwidth(&self) -> u32 {
+        self.bandwidth
+    }
+
+    pub fn set_bandwidth(&mut self, bandwidth: u32) {
+        self.bandwidth = bandwidth;
+    }
+
+    /// Whether the interface is administratively enabled, e.g. not shut down
+    /// for maintenance. Up by default.
+    pub fn is_admin_up(&self) -> bool {
+        self.admin_up
+    }
+
+    pub fn set_admin_up(&mut self, up: bool) {
+        self.admin_up = up;
+    }
+
+    /// Whether the interface actually works, as opposed to having failed.
+    /// Up by default.
+    pub fn is_oper_up(&self) -> bool {
+        self.oper_up
+    }
+
+    pub fn set_oper_up(&mut self, up: bool) {
+        self.oper_up = up;
+    }
+
+    /// Whether traffic can use the interface: it must be both
+    /// administratively and operationally up.
+    pub fn is_up(&self) -> bool {
+        self.admin_up && self.oper_up
+    }
+
+    pub fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    pub fn set_label(&mut self, label: impl Into<String>) {
+        self.label = Some(label.into());
+    }
 }
 
-#[derive(Debug)]
-struct TopologyNode {
+#[derive(Debug, Clone)]
+pub struct TopologyNode {
     id: NodeId,
     ifaces: HashMap<IfaceIndex, Interface>,
+    label: Option<String>,
+}
+
+impl PartialEq for TopologyNode {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id && self.ifaces == other.ifaces
+    }
+}
+
+impl Eq for TopologyNode {}
+
+impl Display for TopologyNode {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match &self.label {
+            Some(label) => f.write_fmt(format_args!("{} ({})", self.id, label)),
+            None => f.write_fmt(format_args!("{}", self.id)),
+        }
+    }
 }
 
 impl TopologyNode {
-    fn new(id: NodeId) -> Self {
+    pub fn new(id: NodeId) -> Self {
         Self {
             id,
             ifaces: HashMap::new(),
+            label: None,
         }
     }
 
-    fn add_iface(&mut self, iface: Interface) {
+    pub fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    pub fn set_label(&mut self, label: impl Into<String>) {
+        self.label = Some(label.into());
+    }
+
+    pub fn add_iface(&mut self, iface: Interface) {
         self.ifaces.insert(iface.id, iface);
     }
+
+    /// Like `add_iface`, but refuses to replace an existing interface or to add
+    /// one that lists this very node as a neighbor.
+    pub fn try_add_iface(&mut self, iface: Interface) -> Result<(), TopologyError> {
+        if self.ifaces.contains_key(&iface.id) {
+            return Err(TopologyError::InterfaceExists(self.id, iface.id));
+        }
+        if iface.neighbors.iter().any(|(neigh_id, _)| *neigh_id == self.id) {
+            return Err(TopologyError::SelfLoop(self.id, iface.id));
+        }
+        self.add_iface(iface);
+        Ok(())
+    }
+
+    pub fn id(&self) -> NodeId {
+        self.id
+    }
+
+    /// Interfaces of the node in ascending `IfaceIndex` order.
+    pub fn interfaces(&self) -> impl Iterator<Item = &Interface> {
+        let mut ifaces: Vec<&Interface> = self.ifaces.values().collect();
+        ifaces.sort_by_key(|iface| iface.id);
+        ifaces.into_iter()
+    }
+
+    /// Whether the node has an `Internet` interface.
+    pub fn is_gateway(&self) -> bool {
+        self.ifaces.values().any(|iface| iface.if_type == InterfaceType::Internet)
+    }
+
+    /// Whether the node has a `LocalApp` interface.
+    pub fn is_app_endpoint(&self) -> bool {
+        self.ifaces.values().any(|iface| iface.if_type == InterfaceType::LocalApp)
+    }
 }
 
-struct Topology {
+/// The network graph. Analyses borrow it immutably; to try out a
+/// destructive change (failing a link, removing a gateway), clone it and
+/// edit the copy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Topology {
     nodes: HashMap<NodeId, TopologyNode>,
 }
 
 impl Topology {
-    fn new() -> Self {
+    pub fn new() -> Self {
         Self {
             nodes: HashMap::new(),
         }
     }
 
-    fn add_node(&mut self, node: TopologyNode) {
+    pub fn add_node(&mut self, node: TopologyNode) {
         self.nodes.insert(node.id, node);
     }
 
-    fn get_node(&self, node_id: NodeId) -> &TopologyNode {
+    /// Removes every node. With the `std` feature the node map keeps its
+    /// allocated capacity, so a topology can be refilled in a loop without
+    /// reallocating each time. Without it the map is a `BTreeMap`, which has
+    /// no capacity to keep.
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+    }
+
+    /// Sets the administrative state of interface `if_id` of `node_id`. An
+    /// admin-down interface keeps its links, but no search uses them.
+    pub fn set_admin_up(&mut self, node_id: NodeId, if_id: IfaceIndex, up: bool) -> Result<(), TopologyError> {
+        self.iface_mut(node_id, if_id)?.set_admin_up(up);
+        Ok(())
+    }
+
+    /// Sets the operational state of interface `if_id` of `node_id`, e.g. to
+    /// model a failure. Down interfaces are avoided like admin-down ones.
+    pub fn set_oper_up(&mut self, node_id: NodeId, if_id: IfaceIndex, up: bool) -> Result<(), TopologyError> {
+        self.iface_mut(node_id, if_id)?.set_oper_up(up);
+        Ok(())
+    }
+
+    fn iface_mut(&mut self, node_id: NodeId, if_id: IfaceIndex) -> Result<&mut Interface, TopologyError> {
+        self.nodes
+            .get_mut(&node_id)
+            .ok_or(TopologyError::NodeNotFound(node_id))?
+            .ifaces
+            .get_mut(&if_id)
+            .ok_or(TopologyError::InterfaceNotFound(node_id, if_id))
+    }
+
+    /// Smallest `NodeId` not in the topology yet. Panics if all 2³² ids are
+    /// taken.
+    pub fn allocate_node_id(&self) -> NodeId {
+        let mut ids: Vec<u32> = self.nodes.keys().map(|id| id.0).collect();
+        ids.sort();
+
+        let mut next: u32 = 0;
+        for id in ids {
+            if id != next {
+                break;
+            }
+            next = next.checked_add(1).expect("NodeId space exhausted");
+        }
+        NodeId(next)
+    }
+
+    /// Links `a_id` and `b_id` with a new `LocalNet` interface on each side.
+    /// Both interface indices must be free, and a node can't be linked to
+    /// itself.
+    pub fn connect(&mut self,
+                   a_id: NodeId,
+                   a_if_id: IfaceIndex,
+                   b_id: NodeId,
+                   b_if_id: IfaceIndex) -> Result<(), TopologyError> {
+        if a_id == b_id {
+            return Err(TopologyError::SelfLoop(a_id, a_if_id));
+        }
+        for (id, if_id) in [(a_id, a_if_id), (b_id, b_if_id)] {
+            let node = self.nodes.get(&id).ok_or(TopologyError::NodeNotFound(id))?;
+            if node.ifaces.contains_key(&if_id) {
+                return Err(TopologyError::InterfaceExists(id, if_id));
+            }
+        }
+
+        self.link(a_id, a_if_id, b_id, b_if_id, InterfaceType::LocalNet);
+        Ok(())
+    }
+
+    /// Like `connect`, but takes the smallest free interface index on each
+    /// node, creates interfaces of type `if_type` and returns the indices
+    /// picked for `a_id` and `b_id`.
+    pub fn connect_auto(&mut self,
+                        a_id: NodeId,
+                        b_id: NodeId,
+                        if_type: InterfaceType) -> Result<(IfaceIndex, IfaceIndex), TopologyError> {
+        for id in [a_id, b_id] {
+            if !self.nodes.contains_key(&id) {
+                return Err(TopologyError::NodeNotFound(id));
+            }
+        }
+
+        let a_if_id = self.next_free_iface(a_id).ok_or(TopologyError::InterfacesExhausted(a_id))?;
+        if a_id == b_id {
+            return Err(TopologyError::SelfLoop(a_id, a_if_id));
+        }
+        let b_if_id = self.next_free_iface(b_id).ok_or(TopologyError::InterfacesExhausted(b_id))?;
+
+        self.link(a_id, a_if_id, b_id, b_if_id, if_type);
+        Ok((a_if_id, b_if_id))
+    }
+
+    /// Records that interface `if_id` of `node_id` reaches `neigh_if_id` of
+    /// `neigh_id`, creating the interface as `LocalNet` if the node doesn't
+    /// have it yet. Only this side is touched, so a link is complete once
+    /// both ends have been reported. Repeated reports are ignored.
+    pub fn add_neighbor(&mut self,
+                        node_id: NodeId,
+                        if_id: IfaceIndex,
+                        neigh_id: NodeId,
+                        neigh_if_id: IfaceIndex) -> Result<(), TopologyError> {
+        if node_id == neigh_id {
+            return Err(TopologyError::SelfLoop(node_id, if_id));
+        }
+        let node = self.nodes.get_mut(&node_id).ok_or(TopologyError::NodeNotFound(node_id))?;
+
+        node.ifaces
+            .entry(if_id)
+            .or_insert_with(|| Interface::new(if_id, InterfaceType::LocalNet, vec![]))
+            .add_neighbor(neigh_id, neigh_if_id);
+        Ok(())
+    }
+
+    /// Puts the given interfaces on one shared segment: each becomes a new
+    /// `SharedMedium` interface listing all the others. Every node may
+    /// appear once, and every interface index must be free. Nothing changes
+    /// on error.
+    pub fn add_segment(&mut self, members: &[(NodeId, IfaceIndex)]) -> Result<(), TopologyError> {
+        let mut seen: HashSet<NodeId> = HashSet::new();
+        for &(id, if_id) in members {
+            let node = self.nodes.get(&id).ok_or(TopologyError::NodeNotFound(id))?;
+            if node.ifaces.contains_key(&if_id) {
+                return Err(TopologyError::InterfaceExists(id, if_id));
+            }
+            if !seen.insert(id) {
+                return Err(TopologyError::DuplicateMember(id));
+            }
+        }
+
+        for &(id, if_id) in members {
+            let others: Vec<(NodeId, IfaceIndex)> = members.iter().copied().filter(|&(other_id, _)| other_id != id).collect();
+            self.get_node_mut(id).add_iface(Interface::new(if_id, InterfaceType::SharedMedium, others));
+        }
+        Ok(())
+    }
+
+    /// Whether `a` and `b` share a segment, i.e. some `SharedMedium`
+    /// interface of `a` lists `b`. Such nodes reach each other directly.
+    pub fn same_segment(&self, a: NodeId, b: NodeId) -> bool {
+        self.nodes.get(&a).is_some_and(|node| {
+            node.ifaces
+                .values()
+                .filter(|iface| iface.if_type == InterfaceType::SharedMedium)
+                .any(|iface| iface.neighbors.iter().any(|&(neigh_id, _)| neigh_id == b))
+        })
+    }
+
+    fn link(&mut self, a_id: NodeId, a_if_id: IfaceIndex, b_id: NodeId, b_if_id: IfaceIndex, if_type: InterfaceType) {
+        self.get_node_mut(a_id).add_iface(Interface::new(a_if_id, if_type, vec![(b_id, b_if_id)]));
+        self.get_node_mut(b_id).add_iface(Interface::new(b_if_id, if_type, vec![(a_id, a_if_id)]));
+    }
+
+    /// Gives every `LocalNet` and `SharedMedium` interface cost `local` and
+    /// every `Internet` interface cost `internet`, e.g. to make weighted
+    /// searches avoid transit. Costs set with `Interface::set_cost` take
+    /// precedence and are left alone, as are `LocalApp` interfaces.
+    /// Interfaces added later start at the usual cost of 1.
+    pub fn set_type_costs(&mut self, local: u32, internet: u32) {
+        for node in self.nodes.values_mut() {
+            for iface in node.ifaces.values_mut() {
+                if iface.explicit_cost {
+                    continue;
+                }
+                match iface.if_type {
+                    InterfaceType::LocalNet | InterfaceType::SharedMedium => iface.cost = local,
+                    InterfaceType::Internet => iface.cost = internet,
+                    InterfaceType::LocalApp => {}
+                }
+            }
+        }
+    }
+
+    /// Captures the whole graph so that it can be brought back with `restore`
+    /// after destructive experiments.
+    pub fn snapshot(&self) -> TopologySnapshot {
+        let mut nodes: Vec<TopologyNode> = self.nodes.values().cloned().collect();
+        nodes.sort_by_key(|node| node.id);
+        TopologySnapshot { nodes }
+    }
+
+    /// Replaces the current graph with the one captured in `snap`.
+    pub fn restore(&mut self, snap: TopologySnapshot) {
+        self.nodes = snap.nodes.into_iter().map(|node| (node.id, node)).collect();
+    }
+
+    pub fn get_node(&self, node_id: NodeId) -> &TopologyNode {
         self.nodes.get(&node_id).unwrap()
     }
 
-    fn get_node_mut(&mut self, node_id: NodeId) -> &mut TopologyNode {
+    pub fn get_node_mut(&mut self, node_id: NodeId) -> &mut TopologyNode {
         self.nodes.get_mut(&node_id).unwrap()
     }
 
-    fn find_internet_gateway(&self) -> Vec<NodeId> {
+    pub fn find_internet_gateway(&self) -> Vec<NodeId> {
         let mut res: Vec<NodeId> = Vec::new();
 
+        for (&n_id, node) in self.nodes.iter() {
+            if node.is_gateway() {
+                res.push(n_id);
+            }
+        }
+        res
+    }
+
+    /// `TopologyNode::is_gateway` of node `id`, or `None` if there is no such
+    /// node.
+    pub fn is_gateway(&self, id: NodeId) -> Option<bool> {
+        self.nodes.get(&id).map(TopologyNode::is_gateway)
+    }
+
+    /// `TopologyNode::is_app_endpoint` of node `id`, or `None` if there is no
+    /// such node.
+    pub fn is_app_endpoint(&self, id: NodeId) -> Option<bool> {
+        self.nodes.get(&id).map(TopologyNode::is_app_endpoint)
+    }
+
+    /// Every node with a `LocalApp` interface, sorted: the valid sources and
+    /// destinations of app traffic.
+    pub fn app_endpoints(&self) -> Vec<NodeId> {
+        self.nodes_where(TopologyNode::is_app_endpoint)
+    }
+
+    /// For every ordered pair of distinct app endpoints, whether any
+    /// `LocalNet` path leads from the first to the second. The `false`
+    /// entries are the connectivity gaps. One BFS per app endpoint.
+    pub fn app_reachability_matrix(&self) -> HashMap<(NodeId, NodeId), bool> {
+        let apps = self.app_endpoints();
+        let mut matrix: HashMap<(NodeId, NodeId), bool> = HashMap::new();
+
+        for &from in &apps {
+            let distances = self.distances_from(from);
+            for &to in apps.iter().filter(|&&to| to != from) {
+                matrix.insert((from, to), distances.contains_key(&to));
+            }
+        }
+        matrix
+    }
+
+    /// Ids of the nodes matching `pred`, sorted.
+    pub fn nodes_where<F: Fn(&TopologyNode) -> bool>(&self, pred: F) -> Vec<NodeId> {
+        let mut res: Vec<NodeId> = self.nodes
+                                       .values()
+                                       .filter(|node| pred(node))
+                                       .map(|node| node.id)
+                                       .collect();
+        res.sort();
+        res
+    }
+
+    /// Every `Internet` interface with its owning node, sorted.
+    pub fn gateway_interfaces(&self) -> Vec<(NodeId, IfaceIndex)> {
+        let mut res: Vec<(NodeId, IfaceIndex)> = Vec::new();
+
         for (&n_id, node) in self.nodes.iter() {
             for iface in node.ifaces.values() {
                 if iface.if_type == InterfaceType::Internet {
-                    res.push(n_id);
-                    break;
+                    res.push((n_id, iface.id));
                 }
             }
         }
+        res.sort();
         res
     }
 
-    fn get_adjacent_interface(&self, from_node: NodeId,
-                              via_if: IfaceIndex,
-                              to_node: NodeId) -> Option<IfaceIndex> {
+    pub fn get_adjacent_interface(&self, from_node: NodeId,
+                                  via_if: IfaceIndex,
+                                  to_node: NodeId) -> Option<IfaceIndex> {
         let node = self.nodes.get(&from_node).unwrap();
         let iface = node.ifaces.get(&via_if).unwrap();
 
@@ -166,173 +743,1069 @@ impl Topology {
         None
     }
 
-    fn get_local_iface_id_type(&self, id: NodeId, if_type: InterfaceType) -> Option<IfaceIndex> {
-        let node = self.nodes.get(&id).unwrap();
-        for (if_id, iface) in &node.ifaces {
-            if iface.if_type == if_type {
-                return Some(*if_id);
+    /// Every `(a_iface, b_iface)` pair of a `LocalNet` link between `a_id` and
+    /// `b_id`, sorted. Empty if they aren't adjacent or either is unknown.
+    pub fn interfaces_between(&self, a_id: NodeId, b_id: NodeId) -> Vec<(IfaceIndex, IfaceIndex)> {
+        self.neighbors(a_id)
+            .filter(|&(_, neigh_id, _)| neigh_id == b_id)
+            .map(|(if_id, _, neigh_if_id)| (if_id, neigh_if_id))
+            .collect()
+    }
+
+    /// Precomputes every adjacency so that repeated lookups are O(1). The index
+    /// borrows the topology, so it can't go stale while it's alive.
+    pub fn build_index(&self) -> TopologyIndex<'_> {
+        let mut adjacency: HashMap<(NodeId, IfaceIndex, NodeId), IfaceIndex> = HashMap::new();
+
+        for (&n_id, node) in self.nodes.iter() {
+            for (&if_id, iface) in node.ifaces.iter() {
+                for &(neigh_id, adj_iface) in &iface.neighbors {
+                    // keep the first match like `get_adjacent_interface` does
+                    adjacency.entry((n_id, if_id, neigh_id)).or_insert(adj_iface);
+                }
             }
         }
-        return None;
+
+        TopologyIndex {
+            topo: self,
+            adjacency,
+        }
     }
 
-    fn get_local_app_iface_id(&self, id: NodeId) -> Option<IfaceIndex> {
-        self.get_local_iface_id_type(id, InterfaceType::LocalApp)
+    /// Interface indices in use on `node`, sorted. Empty for an unknown node.
+    pub fn used_ifaces(&self, node: NodeId) -> Vec<IfaceIndex> {
+        let mut res: Vec<IfaceIndex> = self.nodes
+                                           .get(&node)
+                                           .map(|x| x.ifaces.keys().copied().collect())
+                                           .unwrap_or_default();
+        res.sort();
+        res
     }
 
-    fn get_internet_iface_id(&self, id: NodeId) -> Option<IfaceIndex> {
-        self.get_local_iface_id_type(id, InterfaceType::Internet)
+    /// Smallest interface index not in use on `node`. `None` once all 256
+    /// indices are taken, or if the node doesn't exist.
+    pub fn next_free_iface(&self, node: NodeId) -> Option<IfaceIndex> {
+        let ifaces = &self.nodes.get(&node)?.ifaces;
+        (0..=u8::MAX).map(IfaceIndex).find(|if_id| !ifaces.contains_key(if_id))
     }
 
-    fn check_if_visitted(&self, id: NodeId, path: &Path) -> bool {
-        for node in &path.nodes {
-            if node.id == id {
-                return true;
-            }
-        }
-        false
+    // Smallest interface of `id` of the given type. Panics for an unknown
+    // node.
+    fn get_local_iface_id_type(&self, id: NodeId, if_type: InterfaceType) -> Option<IfaceIndex> {
+        let node = self.nodes.get(&id).unwrap();
+        node.interfaces()
+            .find(|iface| iface.if_type == if_type)
+            .map(|iface| iface.id)
     }
 
-    fn find_path(&self,
-                 start_id: NodeId,
-                 start_if_id: IfaceIndex,
-                 finish_id: NodeId,
-                 finish_if_id: IfaceIndex,
-                 curr_path: &mut Path,
-                 path_vec: &mut Vec<Path>,
-    ) -> bool {
-        // println!("searching path from {start_id} to {finish_id}");
+    pub fn get_local_app_iface_id(&self, id: NodeId) -> Option<IfaceIndex> {
+        self.get_local_iface_id_type(id, InterfaceType::LocalApp)
+    }
 
-        let start_node = self.nodes.get(&start_id).unwrap();
-        let mut path_node = PathNode::new(start_id);
-        path_node.reverse_if_id = start_if_id;
-        curr_path.nodes.push_back(path_node);
+    pub fn get_internet_iface_id(&self, id: NodeId) -> Option<IfaceIndex> {
+        self.get_local_iface_id_type(id, InterfaceType::Internet)
+    }
 
-        if start_id == finish_id {
-            let mut last_node = curr_path.nodes.back_mut().unwrap();
-            last_node.forward_if_id = finish_if_id;
+    /// Every `Internet` interface of `id`, sorted, for nodes with more than
+    /// one egress where `get_internet_iface_id` only gives one. Empty for
+    /// unknown nodes.
+    pub fn egress_interfaces(&self, id: NodeId) -> Vec<IfaceIndex> {
+        let mut res: Vec<IfaceIndex> = self.nodes
+                                           .get(&id)
+                                           .into_iter()
+                                           .flat_map(|node| node.ifaces.values())
+                                           .filter(|iface| iface.if_type == InterfaceType::Internet)
+                                           .map(|iface| iface.id)
+                                           .collect();
+        res.sort();
+        res
+    }
 
-            // println!("found finish node {finish_id}");
-            // println!("{}", curr_path);
+    /// `(local_iface, neighbor_node, neighbor_iface)` for every usable
+    /// `LocalNet` link of the node, ordered by local interface. Links to nodes
+    /// missing from the topology are skipped, as are links with an interface
+    /// that is down on either end, and an unknown `id` yields nothing.
+    pub fn neighbors(&self, id: NodeId) -> impl Iterator<Item = (IfaceIndex, NodeId, IfaceIndex)> + '_ {
+        self.local_links(id)
+            .into_iter()
+            .filter(move |&(if_id, neigh_id, neigh_if_id)| self.nodes[&id].ifaces[&if_id].is_up() && self.peer_is_up(neigh_id, neigh_if_id))
+    }
 
-            path_vec.push(curr_path.clone());
+    // Like `neighbors`, but including links that are down.
+    fn local_links(&self, id: NodeId) -> Vec<(IfaceIndex, NodeId, IfaceIndex)> {
+        let mut links: Vec<(IfaceIndex, NodeId, IfaceIndex)> =
+            self.nodes.get(&id)
+                .into_iter()
+                .flat_map(|node| node.ifaces.values())
+                .filter(|iface| iface.if_type.is_link())
+                .flat_map(|iface| iface.neighbors.iter().map(move |&(neigh_id, neigh_if_id)| (iface.id, neigh_id, neigh_if_id)))
+                .filter(|(_, neigh_id, _)| self.nodes.contains_key(neigh_id))
+                .collect();
+        links.sort();
+        links
+    }
 
-            return true;
-        }
+    // Whether the far end of a link is up. An interface the neighbor doesn't
+    // have can't be down.
+    fn peer_is_up(&self, neigh_id: NodeId, neigh_if_id: IfaceIndex) -> bool {
+        self.nodes
+            .get(&neigh_id)
+            .and_then(|node| node.ifaces.get(&neigh_if_id))
+            .is_none_or(Interface::is_up)
+    }
 
-        let mut ifaces_to_visit: Vec<IfaceIndex> = start_node.ifaces.values()
-                                                                    .filter(|&x| x.if_type == InterfaceType::LocalNet)
-                                                                    .map(|x| x.id)
-                                                                    .collect();
+    fn local_net_neighbor_ids(&self, id: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        self.neighbors(id).map(|(_, neigh_id, _)| neigh_id)
+    }
 
-        let mut flag = false;
+    /// Whether `a` has a usable `LocalNet` link to `b`, checked in
+    /// O(degree of `a`). False for missing nodes.
+    pub fn are_adjacent(&self, a: NodeId, b: NodeId) -> bool {
+        let node = match self.nodes.get(&a) {
+            Some(node) if self.nodes.contains_key(&b) => node,
+            _ => return false,
+        };
+        node.ifaces
+            .values()
+            .filter(|iface| iface.if_type.is_link() && iface.is_up())
+            .flat_map(|iface| iface.neighbors.iter())
+            .any(|&(neigh_id, neigh_if_id)| neigh_id == b && self.peer_is_up(neigh_id, neigh_if_id))
+    }
 
-        for (if_id, iface) in start_node.ifaces.iter() {
-            ifaces_to_visit.retain(|&x| x != *if_id);
+    pub fn is_connected(&self) -> bool {
+        let start_id = match self.nodes.keys().next() {
+            Some(&id) => id,
+            None => return true,
+        };
 
-            for (neigh_id, neigh_if_id) in &iface.neighbors {
-                if !self.check_if_visitted(*neigh_id, curr_path) {
-                    let mut last_node = curr_path.nodes.back_mut().unwrap();
-                    last_node.forward_if_id = *if_id;
-
-                    // println!("visiting {start_id}({if_id}) => {neigh_id}({neigh_if_id})");
-                    if self.find_path(*neigh_id, *neigh_if_id, finish_id, finish_if_id, curr_path, path_vec) {
-                        // println!("found path from {start_id} to {finish_id}");
-                        if !ifaces_to_visit.is_empty() {
-                            let _tail = curr_path.nodes.pop_back().unwrap();
-                            // println!("{start_id}: not all interfaces were visitted. extracting {}", tail.id);
-                            flag = true;
-                            continue;
-                        }
-                        else {
-                            let _tail = curr_path.nodes.pop_back().unwrap();
-                            // println!("{start_id}: all interfaces were visitted. extracting {}", tail.id);
-                            return true;
-                        }
-                    }
-                    else {
-                        let _tail = curr_path.nodes.pop_back().unwrap();
-                        // println!("{start_id}: this is the dead end. extracting {}", tail.id);
-                    }
-                }
-                else {
-                    // println!("{start_id}: neighbor {neigh_id} was already visitted");
+        let mut visited: HashSet<NodeId> = HashSet::from([start_id]);
+        let mut queue: VecDeque<NodeId> = VecDeque::from([start_id]);
+
+        while let Some(id) = queue.pop_front() {
+            for neigh_id in self.local_net_neighbor_ids(id) {
+                if visited.insert(neigh_id) {
+                    queue.push_back(neigh_id);
                 }
             }
-            if !iface.neighbors.is_empty() {
-                // println!("{start_id}: we have seen all available neighbors over interface {if_id}");
-            }
         }
-        // println!("{start_id}: we have seen all available interfaces");
-        flag
+        visited.len() == self.nodes.len()
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    // A(1) -- (1)B(2) -- (1)C
-    fn create_line_topology() -> Topology {
-        let n_a = NodeId(0xA);
-        let n_b = NodeId(0xB);
-        let n_c = NodeId(0xC);
 
-        let if_0 = IfaceIndex(0);
-        let if_1 = IfaceIndex(1);
-        let if_2 = IfaceIndex(2);
-
-        let if_a_0 = Interface::new(if_0, InterfaceType::LocalApp, vec![]);
-        let if_a_1 = Interface::new(if_1, InterfaceType::LocalNet, vec![(n_b, if_1)]);
+    /// New links that would make the topology connected: `c - 1` of them
+    /// for `c` components, each joining the smallest node of one component
+    /// to the smallest node of the next, in `connected_components` order.
+    pub fn links_to_connect(&self) -> Vec<(NodeId, NodeId)> {
+        let representatives: Vec<NodeId> = self.connected_components()
+                                               .into_iter()
+                                               .map(|component| component[0])
+                                               .collect();
+        representatives.windows(2)
+                       .map(|pair| (pair[0], pair[1]))
+                       .collect()
+    }
 
-        let if_b_0 = Interface::new(if_0, InterfaceType::LocalApp, vec![]);
-        let if_b_1 = Interface::new(if_1, InterfaceType::LocalNet, vec![(n_a, if_1)]);
-        let if_b_2 = Interface::new(if_2, InterfaceType::LocalNet, vec![(n_c, if_1)]);
+    /// Node sets of the `LocalNet` graph that can reach each other, every set
+    /// sorted and the sets ordered by their smallest `NodeId`.
+    pub fn connected_components(&self) -> Vec<Vec<NodeId>> {
+        let mut ids: Vec<NodeId> = self.nodes.keys().copied().collect();
+        ids.sort();
 
-        let if_c_0 = Interface::new(if_0, InterfaceType::LocalApp, vec![]);
-        let if_c_1 = Interface::new(if_1, InterfaceType::LocalNet, vec![(n_b, if_2)]);
+        let mut seen: HashSet<NodeId> = HashSet::new();
+        let mut components: Vec<Vec<NodeId>> = Vec::new();
 
-        let mut node_a = TopologyNode::new(n_a);
-        let mut node_b = TopologyNode::new(n_b);
-        let mut node_c = TopologyNode::new(n_c);
+        for id in ids {
+            if seen.contains(&id) {
+                continue;
+            }
 
-        node_a.add_iface(if_a_0);
-        node_a.add_iface(if_a_1);
+            let mut component: Vec<NodeId> = self.distances_from(id).into_keys().collect();
+            component.sort();
+            seen.extend(component.iter().copied());
+            components.push(component);
+        }
+        components
+    }
 
-        node_b.add_iface(if_b_0);
-        node_b.add_iface(if_b_1);
-        node_b.add_iface(if_b_2);
+    /// Appends to `path_vec` the paths the search finds from `start_id`,
+    /// entered through `start_if_id`, to `finish_id`, left through
+    /// `finish_if_id`, and returns whether it found any. `curr_path` is the
+    /// prefix every path starts with, normally empty.
+    ///
+    /// Every node's interfaces are explored in ascending `IfaceIndex` order,
+    /// and the neighbors of one interface in the order they are listed, so
+    /// paths come out in the same order on every run.
+    ///
+    /// Every loop-free path is found: the only thing that cuts a branch short
+    /// is reaching a node that is already on it.
+    ///
+    /// For `start_id == finish_id` the only path is the zero-hop one through
+    /// that node, and only if the interfaces differ: entering and leaving
+    /// through the same interface is not a path.
+    pub fn find_path(&self,
+                     start_id: NodeId,
+                     start_if_id: IfaceIndex,
+                     finish_id: NodeId,
+                     finish_if_id: IfaceIndex,
+                     curr_path: &mut Path,
+                     path_vec: &mut Vec<Path>,
+    ) -> bool {
+        if start_id == finish_id && start_if_id == finish_if_id {
+            return false;
+        }
 
-        node_c.add_iface(if_c_0);
-        node_c.add_iface(if_c_1);
+        let found_before = path_vec.len();
+        let mut on_path: HashSet<NodeId> = curr_path.nodes.iter().map(|x| x.id).collect();
+        let _ = self.find_path_rec(start_id, start_if_id, finish_id, finish_if_id, curr_path, &mut on_path,
+                                   0, &|_| true, &mut PathCollector(path_vec));
+        path_vec.len() > found_before
+    }
 
-        let mut topo = Topology::new();
-        topo.add_node(node_a);
-        topo.add_node(node_b);
-        topo.add_node(node_c);
-        topo
+    /// Collects the paths `find_path` discovers from `start_id`/`start_if_id`
+    /// to `finish_id`/`finish_if_id`. Unlike `find_path`, bad endpoints are
+    /// reported as such rather than as an empty result, and `Ok` always
+    /// holds at least one path.
+    pub fn find_paths(&self,
+                      start_id: NodeId,
+                      start_if_id: IfaceIndex,
+                      finish_id: NodeId,
+                      finish_if_id: IfaceIndex) -> Result<Vec<Path>, PathError> {
+        let mut path_vec: Vec<Path> = Vec::new();
+        self.walk_paths(start_id, start_if_id, finish_id, finish_if_id, &mut PathCollector(&mut path_vec))?;
+        non_empty(path_vec)
     }
 
-    // A(1) -- (1)B(2) -- (1)C(2) -- Internet
-    fn create_line_topology_with_internet() -> Topology {
-        let mut topo = create_line_topology();
-        let if_c_2 = Interface::new(IfaceIndex(2), InterfaceType::Internet, vec![]);
-        let node_c = topo.get_node_mut(NodeId(0xC));
+    /// `find_paths` from app to app: both ends use their `LocalApp`
+    /// interface, the one with the smallest index if there are several.
+    /// A node without one gives `PathError::NoAppInterface`. This is why it
+    /// returns a `Result` like `find_paths` rather than a bare `Vec`: an
+    /// empty list couldn't tell a missing app from a missing route.
+    pub fn find_paths_between(&self, start_id: NodeId, finish_id: NodeId) -> Result<Vec<Path>, PathError> {
+        let start_if_id = self.app_iface_id(start_id, PathError::StartNotFound(start_id))?;
+        let finish_if_id = self.app_iface_id(finish_id, PathError::FinishNotFound(finish_id))?;
+        self.find_paths(start_id, start_if_id, finish_id, finish_if_id)
+    }
 
-        node_c.add_iface(if_c_2);
+    // Smallest `LocalApp` interface of `id`, or `not_found` for an unknown
+    // node.
+    fn app_iface_id(&self, id: NodeId, not_found: PathError) -> Result<IfaceIndex, PathError> {
+        self.nodes
+            .get(&id)
+            .ok_or(not_found)?
+            .interfaces()
+            .find(|iface| iface.if_type == InterfaceType::LocalApp)
+            .map(|iface| iface.id)
+            .ok_or(PathError::NoAppInterface(id))
+    }
 
-        topo
+    /// Like `find_paths`, but every path comes with its hop count and cost,
+    /// worked out as each path is found rather than in a second pass.
+    pub fn find_paths_detailed(&self,
+                               start_id: NodeId,
+                               start_if_id: IfaceIndex,
+                               finish_id: NodeId,
+                               finish_if_id: IfaceIndex) -> Result<Vec<PathResult>, PathError> {
+        let mut results: Vec<PathResult> = Vec::new();
+        self.walk_paths(start_id, start_if_id, finish_id, finish_if_id, &mut DetailedPathCollector { results: &mut results })?;
+        if results.is_empty() {
+            return Err(PathError::NoRoute);
+        }
+        Ok(results)
     }
 
-    // Internet -- (2)A(1) -- (1)B(2) -- (1)C(2) -- Internet
-    fn create_line_topology_with_internet_2() -> Topology {
-        let mut topo = create_line_topology_with_internet();
-        let if_a_2 = Interface::new(IfaceIndex(2), InterfaceType::Internet, vec![]);
-        let node_a = topo.get_node_mut(NodeId(0xA));
+    // Rejects endpoints no search could start from or end at.
+    fn check_endpoints(&self,
+                       start_id: NodeId,
+                       start_if_id: IfaceIndex,
+                       finish_id: NodeId,
+                       finish_if_id: IfaceIndex) -> Result<(), PathError> {
+        let start_node = self.nodes.get(&start_id).ok_or(PathError::StartNotFound(start_id))?;
+        let finish_node = self.nodes.get(&finish_id).ok_or(PathError::FinishNotFound(finish_id))?;
+        if !start_node.ifaces.contains_key(&start_if_id) {
+            return Err(PathError::StartInterfaceInvalid(start_id, start_if_id));
+        }
+        if !finish_node.ifaces.contains_key(&finish_if_id) {
+            return Err(PathError::FinishInterfaceInvalid(finish_id, finish_if_id));
+        }
+        Ok(())
+    }
 
-        node_a.add_iface(if_a_2);
+    /// Like `find_paths`, but only ever leaves a node through `LocalNet`
+    /// interfaces, so no path transits an `Internet` or `LocalApp` interface
+    /// even if it lists neighbors.
+    ///
+    /// `start_if_id` and `finish_if_id` are endpoints rather than transit hops
+    /// and may be of any type, e.g. routing from an app port to an egress.
+    ///
+    /// Interface type preference has no say in `shortest_path` and the other
+    /// shortest-path searches: they only follow `LocalNet` links to begin
+    /// with. To bias a choice between routes, give interfaces a `cost`.
+    pub fn find_paths_local_only(&self,
+                                 start_id: NodeId,
+                                 start_if_id: IfaceIndex,
+                                 finish_id: NodeId,
+                                 finish_if_id: IfaceIndex) -> Result<Vec<Path>, PathError> {
+        self.find_paths_filtered(start_id, start_if_id, finish_id, finish_if_id,
+                                 |step| step.exit.if_type.is_link())
+    }
 
-        topo
+    /// The opposite preference to `find_paths_local_only`: the paths of
+    /// `find_paths`, ordered so the ones with the fewest hops over local
+    /// links come first, however many hops they take in all. Routes that
+    /// cut across through `Internet` interfaces listing neighbors thus rank
+    /// ahead of ones that stay local. Ties keep the `find_paths` order. As
+    /// with the local-only search, the shortest-path searches ignore this.
+    pub fn find_paths_prefer_internet(&self,
+                                      start_id: NodeId,
+                                      start_if_id: IfaceIndex,
+                                      finish_id: NodeId,
+                                      finish_if_id: IfaceIndex) -> Result<Vec<Path>, PathError> {
+        let mut paths = self.find_paths(start_id, start_if_id, finish_id, finish_if_id)?;
+        paths.sort_by_key(|path| {
+            path.nodes
+                .iter()
+                .take(path.nodes.len() - 1)
+                .filter(|x| self.nodes[&x.id].ifaces[&x.forward_if_id].if_type.is_link())
+                .count()
+        });
+        Ok(paths)
+    }
+
+    /// Like `find_paths`, but only through the nodes in `allowed`, e.g. to
+    /// keep routes inside one region or tenant. Nodes outside it are treated
+    /// as if they didn't exist, so if the start or finish isn't allowed
+    /// there is no route.
+    pub fn find_paths_within(&self,
+                             start_id: NodeId,
+                             start_if_id: IfaceIndex,
+                             finish_id: NodeId,
+                             finish_if_id: IfaceIndex,
+                             allowed: &HashSet<NodeId>) -> Result<Vec<Path>, PathError> {
+        self.check_endpoints(start_id, start_if_id, finish_id, finish_if_id)?;
+        if !allowed.contains(&start_id) {
+            return Err(PathError::NoRoute);
+        }
+        self.find_paths_filtered(start_id, start_if_id, finish_id, finish_if_id,
+                                 |step| allowed.contains(&step.next))
+    }
+
+    /// Like `find_paths`, but only takes the steps `allow_step` accepts.
+    /// Every constrained search is a filter on this one, see `StepContext`
+    /// for what a filter gets to look at.
+    pub fn find_paths_filtered<F>(&self,
+                                  start_id: NodeId,
+                                  start_if_id: IfaceIndex,
+                                  finish_id: NodeId,
+                                  finish_if_id: IfaceIndex,
+                                  allow_step: F) -> Result<Vec<Path>, PathError>
+    where
+        F: Fn(&StepContext) -> bool,
+    {
+        self.check_endpoints(start_id, start_if_id, finish_id, finish_if_id)?;
+        if start_id == finish_id && start_if_id == finish_if_id {
+            return Err(PathError::NoRoute);
+        }
+        non_empty(self.filtered_paths(start_id, start_if_id, finish_id, finish_if_id, allow_step))
+    }
+
+    // The search behind `find_paths_filtered`, without its endpoint checks.
+    // A start that is the finish gives the one-node path.
+    fn filtered_paths<F>(&self,
+                         start_id: NodeId,
+                         start_if_id: IfaceIndex,
+                         finish_id: NodeId,
+                         finish_if_id: IfaceIndex,
+                         allow_step: F) -> Vec<Path>
+    where
+        F: Fn(&StepContext) -> bool,
+    {
+        let mut path_vec: Vec<Path> = Vec::new();
+        let _ = self.find_path_rec(start_id, start_if_id, finish_id, finish_if_id,
+                                   &mut Path::new(), &mut HashSet::new(), 0, &allow_step, &mut PathCollector(&mut path_vec));
+        path_vec
+    }
+
+    /// Like `find_paths`, but a node may appear up to `max_visits_per_node`
+    /// times on a path, e.g. re-entering it through another interface. No
+    /// link is crossed twice on one path, so a path never just goes back
+    /// and forth over the link it came in on. A path ends as soon as it
+    /// reaches `finish_id`. Since no node is visited more often, no path is
+    /// longer than `max_visits_per_node` times the node count, which bounds
+    /// the search; expect it to grow quickly with the limit all the same. A
+    /// limit of 1 gives exactly `find_paths`, 0 finds nothing.
+    pub fn find_paths_allow_revisit(&self,
+                                    start_id: NodeId,
+                                    start_if_id: IfaceIndex,
+                                    finish_id: NodeId,
+                                    finish_if_id: IfaceIndex,
+                                    max_visits_per_node: usize) -> Result<Vec<Path>, PathError> {
+        self.check_endpoints(start_id, start_if_id, finish_id, finish_if_id)?;
+
+        let mut path_vec: Vec<Path> = Vec::new();
+        if max_visits_per_node == 0 || (start_id == finish_id && start_if_id == finish_if_id) {
+            return Err(PathError::NoRoute);
+        }
+
+        let mut curr_path = Path::new();
+        let mut path_node = PathNode::new(start_id);
+        path_node.reverse_if_id = start_if_id;
+        curr_path.nodes.push_back(path_node);
+
+        let mut visits: HashMap<NodeId, usize> = HashMap::from([(start_id, 1)]);
+        self.find_paths_allow_revisit_rec(finish_id, finish_if_id, max_visits_per_node,
+                                          &mut curr_path, &mut visits, &mut HashSet::new(), &mut path_vec);
+        non_empty(path_vec)
+    }
+
+    // `used_links` holds the links on `curr_path`, each from its smaller
+    // `(node, interface)` end.
+    #[allow(clippy::too_many_arguments)]
+    fn find_paths_allow_revisit_rec(&self,
+                                    finish_id: NodeId,
+                                    finish_if_id: IfaceIndex,
+                                    max_visits_per_node: usize,
+                                    curr_path: &mut Path,
+                                    visits: &mut HashMap<NodeId, usize>,
+                                    used_links: &mut HashSet<(NodeId, IfaceIndex, NodeId, IfaceIndex)>,
+                                    path_vec: &mut Vec<Path>) {
+        let id = curr_path.nodes.back().unwrap().id;
+        if id == finish_id {
+            curr_path.nodes.back_mut().unwrap().forward_if_id = finish_if_id;
+            path_vec.push(curr_path.clone());
+            return;
+        }
+
+        for iface in self.nodes[&id].interfaces().filter(|iface| iface.is_up()) {
+            for &(neigh_id, neigh_if_id) in &iface.neighbors {
+                if !self.nodes.contains_key(&neigh_id) || !self.peer_is_up(neigh_id, neigh_if_id) {
+                    continue;
+                }
+                let link = if (id, iface.id) <= (neigh_id, neigh_if_id) {
+                    (id, iface.id, neigh_id, neigh_if_id)
+                } else {
+                    (neigh_id, neigh_if_id, id, iface.id)
+                };
+                let count = visits.entry(neigh_id).or_insert(0);
+                if *count >= max_visits_per_node || used_links.contains(&link) {
+                    continue;
+                }
+                *count += 1;
+                used_links.insert(link);
+
+                curr_path.nodes.back_mut().unwrap().forward_if_id = iface.id;
+                let mut path_node = PathNode::new(neigh_id);
+                path_node.reverse_if_id = neigh_if_id;
+                curr_path.nodes.push_back(path_node);
+
+                self.find_paths_allow_revisit_rec(finish_id, finish_if_id, max_visits_per_node, curr_path, visits, used_links, path_vec);
+
+                curr_path.nodes.pop_back();
+                *visits.get_mut(&neigh_id).unwrap() -= 1;
+                used_links.remove(&link);
+            }
+        }
+    }
+
+    // `on_path` mirrors the node ids of `curr_path` so that the visited check
+    // doesn't have to scan the path on every step. `allow_step` sees every
+    // step the search is about to take; rejected steps are never taken.
+    // `cost` is what the steps so far added up to. Every path found goes to
+    // `visitor`, and the search unwinds as soon as it says to stop.
+    #[allow(clippy::too_many_arguments)]
+    fn find_path_rec(&self,
+                     start_id: NodeId,
+                     start_if_id: IfaceIndex,
+                     finish_id: NodeId,
+                     finish_if_id: IfaceIndex,
+                     curr_path: &mut Path,
+                     on_path: &mut HashSet<NodeId>,
+                     cost: u32,
+                     allow_step: &dyn Fn(&StepContext) -> bool,
+                     visitor: &mut dyn PathVisitor,
+    ) -> ControlFlow<()> {
+        // println!("searching path from {start_id} to {finish_id}");
+
+        let start_node = self.nodes.get(&start_id).unwrap();
+        let mut path_node = PathNode::new(start_id);
+        path_node.reverse_if_id = start_if_id;
+        curr_path.nodes.push_back(path_node);
+        on_path.insert(start_id);
+        visitor.on_enter(start_id);
+
+        if start_id == finish_id {
+            let last_node = curr_path.nodes.back_mut().unwrap();
+            last_node.forward_if_id = finish_if_id;
+
+            // println!("found finish node {finish_id}");
+            // println!("{}", curr_path);
+
+            return visitor.on_complete(curr_path, curr_path.nodes.len() - 1, cost);
+        }
+
+        for iface in start_node.interfaces().filter(|iface| iface.is_up()) {
+            for (neigh_id, neigh_if_id) in &iface.neighbors {
+                if !self.nodes.contains_key(neigh_id) || !self.peer_is_up(*neigh_id, *neigh_if_id) {
+                    continue;
+                }
+                if on_path.contains(neigh_id) {
+                    // println!("{start_id}: neighbor {neigh_id} was already visitted");
+                    visitor.on_prune(*neigh_id);
+                    continue;
+                }
+
+                let step = StepContext {
+                    node: start_id,
+                    entry: start_node.ifaces.get(&start_if_id),
+                    exit: iface,
+                    next: *neigh_id,
+                    next_if_id: *neigh_if_id,
+                    cost,
+                    hops: curr_path.nodes.len() - 1,
+                };
+                if !allow_step(&step) {
+                    continue;
+                }
+
+                let last_node = curr_path.nodes.back_mut().unwrap();
+                last_node.forward_if_id = iface.id;
+
+                // println!("visiting {start_id}({}) => {neigh_id}({neigh_if_id})", iface.id);
+                let flow = self.find_path_rec(*neigh_id, *neigh_if_id, finish_id, finish_if_id, curr_path, on_path,
+                                              cost.saturating_add(iface.cost), allow_step, visitor);
+
+                let tail = curr_path.nodes.pop_back().unwrap();
+                on_path.remove(&tail.id);
+                visitor.on_leave(tail.id);
+                flow?;
+            }
+        }
+        // println!("{start_id}: we have seen all available interfaces");
+        ControlFlow::Continue(())
+    }
+
+    /// Number of loop-free `LocalNet` paths from `start_id` to `finish_id`,
+    /// counted without building any `Path`. Parallel links make distinct
+    /// paths. The search is still exponential on dense graphs, where the count
+    /// itself can outgrow `usize`; it saturates at `usize::MAX` instead of
+    /// wrapping.
+    pub fn count_paths(&self, start_id: NodeId, finish_id: NodeId) -> usize {
+        if !self.nodes.contains_key(&start_id) || !self.nodes.contains_key(&finish_id) {
+            return 0;
+        }
+        self.count_paths_rec(start_id, finish_id, &mut HashSet::from([start_id]))
+    }
+
+    fn count_paths_rec(&self, id: NodeId, finish_id: NodeId, on_path: &mut HashSet<NodeId>) -> usize {
+        if id == finish_id {
+            return 1;
+        }
+
+        let mut count: usize = 0;
+        for neigh_id in self.local_net_neighbor_ids(id) {
+            if on_path.insert(neigh_id) {
+                count = count.saturating_add(self.count_paths_rec(neigh_id, finish_id, on_path));
+                on_path.remove(&neigh_id);
+            }
+        }
+        count
+    }
+
+    /// Loop-free `LocalNet` path with the most hops, e.g. for worst-case
+    /// latency analysis. This tries every simple path and is exponential (the
+    /// problem is NP-hard), so on anything but small graphs prefer
+    /// `longest_path_within` with a hop bound around the diameter.
+    pub fn longest_path(&self, start_id: NodeId, finish_id: NodeId) -> Option<Path> {
+        self.longest_path_within(start_id, finish_id, usize::MAX)
+    }
+
+    /// Like `longest_path`, but never extends a path beyond `max_hops` hops.
+    /// Endpoint interfaces of the returned path are left at their defaults.
+    pub fn longest_path_within(&self, start_id: NodeId, finish_id: NodeId, max_hops: usize) -> Option<Path> {
+        if !self.nodes.contains_key(&start_id) || !self.nodes.contains_key(&finish_id) {
+            return None;
+        }
+
+        let mut curr_path = Path::new();
+        curr_path.nodes.push_back(PathNode::new(start_id));
+        let mut longest: Option<Path> = None;
+        self.longest_path_rec(finish_id, max_hops, &mut curr_path, &mut HashSet::from([start_id]), &mut longest);
+        longest
+    }
+
+    fn longest_path_rec(&self,
+                        finish_id: NodeId,
+                        max_hops: usize,
+                        curr_path: &mut Path,
+                        on_path: &mut HashSet<NodeId>,
+                        longest: &mut Option<Path>) {
+        let id = curr_path.nodes.back().unwrap().id;
+        if id == finish_id {
+            if longest.as_ref().is_none_or(|x| curr_path.nodes.len() > x.nodes.len()) {
+                *longest = Some(curr_path.clone());
+            }
+            return;
+        }
+        if curr_path.nodes.len() > max_hops {
+            return;
+        }
+
+        for (if_id, neigh_id, neigh_if_id) in self.neighbors(id) {
+            if !on_path.insert(neigh_id) {
+                continue;
+            }
+
+            curr_path.nodes.back_mut().unwrap().forward_if_id = if_id;
+            let mut path_node = PathNode::new(neigh_id);
+            path_node.reverse_if_id = neigh_if_id;
+            curr_path.nodes.push_back(path_node);
+
+            self.longest_path_rec(finish_id, max_hops, curr_path, on_path, longest);
+
+            curr_path.nodes.pop_back();
+            curr_path.nodes.back_mut().unwrap().forward_if_id = IfaceIndex::default();
+            on_path.remove(&neigh_id);
+        }
+    }
+
+    /// Every loop-free `LocalNet` path whose `total_cost` is at most
+    /// `budget`, in depth-first order with interfaces ascending. A branch is
+    /// dropped as soon as its cost so far exceeds the budget. Endpoint
+    /// interfaces are left at their defaults.
+    pub fn paths_within_budget(&self, start_id: NodeId, finish_id: NodeId, budget: u32) -> Vec<Path> {
+        if !self.nodes.contains_key(&start_id) || !self.nodes.contains_key(&finish_id) {
+            return Vec::new();
+        }
+        self.filtered_paths(start_id, IfaceIndex::default(), finish_id, IfaceIndex::default(),
+                            |step| step.exit.if_type.is_link() && step.cost.saturating_add(step.exit.cost) <= budget)
+    }
+
+    // Dijkstra over LocalNet links from `start_id`. `step` extends the key of a
+    // partial path by one hop (from, via_if, to, to_if) or forbids the hop by
+    // returning `None`; it must never make the key smaller. Stops as soon as
+    // `finish_id` is settled, otherwise settles every reachable node.
+    fn dijkstra<K, F>(&self, start_id: NodeId, finish_id: Option<NodeId>, step: F) -> (HashMap<NodeId, K>, Predecessors)
+    where
+        K: Ord + Copy + Default,
+        F: Fn(K, NodeId, IfaceIndex, NodeId, IfaceIndex) -> Option<K>,
+    {
+        self.dijkstra_over(start_id, finish_id, |id| self.neighbors(id).collect(), step)
+    }
+
+    // `dijkstra` over the hops `hops` lists for each node instead of the
+    // LocalNet links.
+    fn dijkstra_over<K, H, F>(&self,
+                              start_id: NodeId,
+                              finish_id: Option<NodeId>,
+                              hops: H,
+                              step: F) -> (HashMap<NodeId, K>, Predecessors)
+    where
+        K: Ord + Copy + Default,
+        H: Fn(NodeId) -> Vec<(IfaceIndex, NodeId, IfaceIndex)>,
+        F: Fn(K, NodeId, IfaceIndex, NodeId, IfaceIndex) -> Option<K>,
+    {
+        let mut best: HashMap<NodeId, K> = HashMap::new();
+        let mut prev: Predecessors = HashMap::new();
+        if !self.nodes.contains_key(&start_id) {
+            return (best, prev);
+        }
+
+        best.insert(start_id, K::default());
+        let mut heap = BinaryHeap::from([Reverse((K::default(), start_id))]);
+
+        while let Some(Reverse((key, id))) = heap.pop() {
+            if best.get(&id).is_some_and(|&best_key| key > best_key) {
+                continue;
+            }
+            if finish_id == Some(id) {
+                break;
+            }
+
+            for (if_id, neigh_id, neigh_if_id) in hops(id) {
+                let next_key = match step(key, id, if_id, neigh_id, neigh_if_id) {
+                    Some(next_key) => next_key,
+                    None => continue,
+                };
+                if best.get(&neigh_id).is_none_or(|&best_key| next_key < best_key) {
+                    best.insert(neigh_id, next_key);
+                    prev.insert(neigh_id, (id, if_id, neigh_if_id));
+                    heap.push(Reverse((next_key, neigh_id)));
+                }
+            }
+        }
+        (best, prev)
+    }
+
+    // Single-pair `dijkstra`. The endpoint interfaces of the returned path are
+    // left at their defaults.
+    fn cheapest_path<K, F>(&self, start_id: NodeId, finish_id: NodeId, step: F) -> Option<(Path, K)>
+    where
+        K: Ord + Copy + Default,
+        F: Fn(K, NodeId, IfaceIndex, NodeId, IfaceIndex) -> Option<K>,
+    {
+        if !self.nodes.contains_key(&finish_id) {
+            return None;
+        }
+
+        let (best, prev) = self.dijkstra(start_id, Some(finish_id), step);
+        let &key = best.get(&finish_id)?;
+        Some((self.path_from_predecessors(start_id, finish_id, &prev), key))
+    }
+
+    fn link_cost(&self, id: NodeId, if_id: IfaceIndex) -> u32 {
+        self.nodes[&id].ifaces[&if_id].cost
+    }
+
+    /// Hop count of the shortest `LocalNet` path from `start_id` to every
+    /// reachable node, `start_id` itself included. Unreachable nodes are
+    /// omitted.
+    pub fn distances_from(&self, start_id: NodeId) -> HashMap<NodeId, usize> {
+        let mut distances: HashMap<NodeId, usize> = HashMap::new();
+        if !self.nodes.contains_key(&start_id) {
+            return distances;
+        }
+
+        distances.insert(start_id, 0);
+        let mut queue: VecDeque<NodeId> = VecDeque::from([start_id]);
+
+        while let Some(id) = queue.pop_front() {
+            let next_distance = distances[&id] + 1;
+            for neigh_id in self.local_net_neighbor_ids(id) {
+                distances.entry(neigh_id).or_insert_with(|| {
+                    queue.push_back(neigh_id);
+                    next_distance
+                });
+            }
+        }
+        distances
+    }
+
+    /// Nodes at most `k` hops from `start_id` over `LocalNet` links with their
+    /// hop count, `start_id` itself at 0, ordered by distance and then id. The
+    /// search stops at depth `k` instead of exploring the whole component.
+    pub fn nodes_within(&self, start_id: NodeId, k: usize) -> Vec<(NodeId, usize)> {
+        let mut res: Vec<(NodeId, usize)> = Vec::new();
+        if !self.nodes.contains_key(&start_id) {
+            return res;
+        }
+
+        let mut seen: HashSet<NodeId> = HashSet::from([start_id]);
+        let mut frontier: Vec<NodeId> = vec![start_id];
+
+        for distance in 0..=k {
+            frontier.sort();
+            res.extend(frontier.iter().map(|&id| (id, distance)));
+            if distance == k {
+                break;
+            }
+
+            frontier = frontier.iter()
+                               .flat_map(|&id| self.local_net_neighbor_ids(id))
+                               .filter(|&neigh_id| seen.insert(neigh_id))
+                               .collect();
+            if frontier.is_empty() {
+                break;
+            }
+        }
+        res
+    }
+
+    /// Like `distances_from`, but sums the `cost` of the interfaces a path
+    /// leaves through instead of counting hops.
+    pub fn distances_from_weighted(&self, start_id: NodeId) -> HashMap<NodeId, u32> {
+        let (best, _) = self.dijkstra(start_id, None, |cost: u32, from, via_if, _, _| {
+            Some(cost.saturating_add(self.link_cost(from, via_if)))
+        });
+        best
+    }
+
+    fn path_from_predecessors(&self,
+                              start_id: NodeId,
+                              finish_id: NodeId,
+                              prev: &Predecessors) -> Path {
+        let mut path = Path::new();
+        let mut id = finish_id;
+        path.nodes.push_front(PathNode::new(finish_id));
+
+        while id != start_id {
+            let (prev_id, prev_if_id, if_id) = prev[&id];
+            path.nodes.front_mut().unwrap().reverse_if_id = if_id;
+
+            let mut path_node = PathNode::new(prev_id);
+            path_node.forward_if_id = prev_if_id;
+            path.nodes.push_front(path_node);
+            id = prev_id;
+        }
+        path
+    }
+
+    /// Checks that `path` can still be walked in this topology: every node
+    /// exists, appears once, and each hop leaves and enters through
+    /// interfaces that list each other as neighbors. The start and finish
+    /// interfaces aren't hops and aren't checked. An empty path is invalid.
+    pub fn is_valid_path(&self, path: &Path) -> bool {
+        if path.nodes.is_empty() {
+            return false;
+        }
+
+        let mut seen: HashSet<NodeId> = HashSet::new();
+        if !path.nodes.iter().all(|x| self.nodes.contains_key(&x.id) && seen.insert(x.id)) {
+            return false;
+        }
+
+        let links = |id: NodeId, if_id: IfaceIndex, neigh: (NodeId, IfaceIndex)| {
+            self.nodes[&id].ifaces.get(&if_id).is_some_and(|iface| iface.neighbors.contains(&neigh))
+        };
+        path.nodes.iter().zip(path.nodes.iter().skip(1)).all(|(from, to)| {
+            links(from.id, from.forward_if_id, (to.id, to.reverse_if_id))
+                && links(to.id, to.reverse_if_id, (from.id, from.forward_if_id))
+        })
+    }
+
+    /// Path with the fewest hops over `LocalNet` links, if any.
+    pub fn shortest_path(&self, start_id: NodeId, finish_id: NodeId) -> Option<Path> {
+        self.cheapest_path(start_id, finish_id, |hops: usize, _, _, _, _| Some(hops + 1))
+            .map(|(path, _)| path)
+    }
+
+    /// Like `shortest_path`, but only over links whose interfaces on both
+    /// ends have at least `min_bw` bandwidth; narrower links are treated as
+    /// absent.
+    pub fn shortest_path_min_bandwidth(&self, start_id: NodeId, finish_id: NodeId, min_bw: u32) -> Option<Path> {
+        self.cheapest_path(start_id, finish_id, |hops: usize, from, via_if, to, to_if| {
+            let wide_enough = self.nodes[&from].ifaces[&via_if].bandwidth >= min_bw
+                && self.nodes[&to].ifaces.get(&to_if).is_some_and(|iface| iface.bandwidth >= min_bw);
+            wide_enough.then_some(hops + 1)
+        }).map(|(path, _)| path)
+    }
+
+    /// Path with the smallest total `cost`, if any. Besides `LocalNet`
+    /// links it may cross `Internet` interfaces that list neighbors, as
+    /// `find_paths` does, at their own `cost`; `set_type_costs` can thus
+    /// make Internet transit lose to a longer local route, or win.
+    pub fn shortest_path_weighted(&self, start_id: NodeId, finish_id: NodeId) -> Option<Path> {
+        if !self.nodes.contains_key(&finish_id) {
+            return None;
+        }
+
+        let step = |cost: u32, from, via_if, _, _| Some(cost.saturating_add(self.link_cost(from, via_if)));
+        let (best, prev) = self.dijkstra_over(start_id, Some(finish_id), |id| self.transit_hops(id), step);
+        best.contains_key(&finish_id).then(|| self.path_from_predecessors(start_id, finish_id, &prev))
+    }
+
+    // Like `neighbors`, plus usable hops out of `Internet` interfaces that
+    // list neighbors.
+    fn transit_hops(&self, id: NodeId) -> Vec<(IfaceIndex, NodeId, IfaceIndex)> {
+        let mut hops: Vec<(IfaceIndex, NodeId, IfaceIndex)> =
+            self.nodes.get(&id)
+                .into_iter()
+                .flat_map(|node| node.ifaces.values())
+                .filter(|iface| iface.if_type.is_link() || iface.if_type == InterfaceType::Internet)
+                .filter(|iface| iface.is_up())
+                .flat_map(|iface| iface.neighbors.iter().map(move |&(neigh_id, neigh_if_id)| (iface.id, neigh_id, neigh_if_id)))
+                .filter(|&(_, neigh_id, neigh_if_id)| self.nodes.contains_key(&neigh_id) && self.peer_is_up(neigh_id, neigh_if_id))
+                .collect();
+        hops.sort();
+        hops
+    }
+
+    /// Among the fewest-hop paths, the one passing through the fewest
+    /// gateways, e.g. to keep east-west traffic away from egress points.
+    /// The endpoints are on every candidate and don't change the choice.
+    pub fn path_min_transit(&self, start_id: NodeId, finish_id: NodeId) -> Option<Path> {
+        self.cheapest_path(start_id, finish_id, |(hops, transit): (usize, usize), _, _, to, _| {
+            Some((hops + 1, transit + usize::from(self.nodes[&to].is_gateway())))
+        }).map(|(path, _)| path)
+    }
+
+    /// Cheapest path by total `cost` among those with at most `max_hops`
+    /// hops, e.g. to respect a TTL, with its cost. Among equally cheap
+    /// paths the one with the fewest hops wins. `None` if no path fits.
+    ///
+    /// Label-setting search over `(node, hops)` states: a state is dropped
+    /// when the node was already settled at no more cost in no more hops.
+    pub fn cheapest_path_bounded_hops(&self, start_id: NodeId, finish_id: NodeId, max_hops: usize) -> Option<(Path, u32)> {
+        if !self.nodes.contains_key(&start_id) || !self.nodes.contains_key(&finish_id) {
+            return None;
+        }
+
+        let mut settled_hops: HashMap<NodeId, usize> = HashMap::new();
+        let mut prev: HashMap<(NodeId, usize), (NodeId, IfaceIndex, IfaceIndex)> = HashMap::new();
+        let mut heap = BinaryHeap::from([Reverse((0, 0, start_id, None))]);
+
+        while let Some(Reverse((cost, hops, id, link))) = heap.pop() {
+            // popped in (cost, hops) order, so anything settled before is no
+            // more expensive
+            if settled_hops.get(&id).is_some_and(|&settled| settled <= hops) {
+                continue;
+            }
+            settled_hops.insert(id, hops);
+            if let Some(link) = link {
+                prev.insert((id, hops), link);
+            }
+
+            if id == finish_id {
+                let mut path = Path::new();
+                path.nodes.push_front(PathNode::new(finish_id));
+                for h in (1..=hops).rev() {
+                    let (prev_id, prev_if_id, if_id) = prev[&(path.nodes[0].id, h)];
+                    path.nodes[0].reverse_if_id = if_id;
+                    let mut path_node = PathNode::new(prev_id);
+                    path_node.forward_if_id = prev_if_id;
+                    path.nodes.push_front(path_node);
+                }
+                return Some((path, cost));
+            }
+            if hops == max_hops {
+                continue;
+            }
+
+            for (if_id, neigh_id, neigh_if_id) in self.neighbors(id) {
+                if settled_hops.get(&neigh_id).is_some_and(|&settled| settled <= hops + 1) {
+                    continue;
+                }
+                let next_cost = cost.saturating_add(self.link_cost(id, if_id));
+                heap.push(Reverse((next_cost, hops + 1, neigh_id, Some((id, if_id, neigh_if_id)))));
+            }
+        }
+        None
+    }
+
+    /// Tree of cheapest routes from `root` as a topology of its own, e.g. to
+    /// render how traffic from `root` flows. Only the `LocalNet` links of the
+    /// tree are kept, with their original interface indices and costs; other
+    /// interfaces are copied as is. Nodes unreachable from `root` are left out.
+    pub fn shortest_path_tree(&self, root: NodeId) -> Topology {
+        let (best, prev) = self.dijkstra(root, None, |cost: u32, from, via_if, _, _| {
+            Some(cost.saturating_add(self.link_cost(from, via_if)))
+        });
+
+        let mut tree = Topology::new();
+        for id in best.keys() {
+            let mut node = TopologyNode::new(*id);
+            for iface in self.nodes[id].ifaces.values() {
+                if !iface.if_type.is_link() {
+                    node.add_iface(iface.clone());
+                }
+            }
+            tree.add_node(node);
+        }
+
+        for (&id, &(prev_id, prev_if_id, if_id)) in prev.iter() {
+            for (from, from_if, to, to_if) in [(prev_id, prev_if_id, id, if_id), (id, if_id, prev_id, prev_if_id)] {
+                tree.get_node_mut(from)
+                    .ifaces
+                    .entry(from_if)
+                    .or_insert_with(|| Interface {
+                        neighbors: Vec::new(),
+                        ..self.nodes[&from].ifaces[&from_if].clone()
+                    })
+                    .neighbors
+                    .push((to, to_if));
+            }
+        }
+        tree
+    }
+
+    /// Route from `start_id` to `finish_id` to fall back on when `primary` fails.
+    ///
+    /// A path sharing no intermediate node with `primary` is preferred. If none
+    /// exists, the path sharing the fewest intermediate nodes and links is
+    /// returned instead, the shortest one among equals. `None` means `primary`
+    /// is the only route. Endpoint interfaces are copied from `primary`.
+    pub fn backup_path(&self, start_id: NodeId, finish_id: NodeId, primary: &Path) -> Option<Path> {
+        let shared_nodes: HashSet<NodeId> = primary.nodes.iter()
+                                                   .map(|x| x.id)
+                                                   .filter(|&id| id != start_id && id != finish_id)
+                                                   .collect();
+        let mut shared_links: HashSet<(NodeId, IfaceIndex, NodeId, IfaceIndex)> = HashSet::new();
+        for pair in primary.nodes.iter().collect::<Vec<_>>().windows(2) {
+            shared_links.insert((pair[0].id, pair[0].forward_if_id, pair[1].id, pair[1].reverse_if_id));
+            shared_links.insert((pair[1].id, pair[1].reverse_if_id, pair[0].id, pair[0].forward_if_id));
+        }
+
+        let (mut path, _) = self.cheapest_path(start_id, finish_id, |(overlap, hops): (usize, usize), from, via_if, to, to_if| {
+            let overlap = overlap
+                + usize::from(shared_nodes.contains(&to))
+                + usize::from(shared_links.contains(&(from, via_if, to, to_if)));
+            Some((overlap, hops + 1))
+        })?;
+
+        if let (Some(first), Some(last)) = (primary.nodes.front(), primary.nodes.back()) {
+            path.nodes.front_mut().unwrap().reverse_if_id = first.reverse_if_id;
+            path.nodes.back_mut().unwrap().forward_if_id = last.forward_if_id;
+        }
+
+        if path == *primary {
+            return None;
+        }
+        Some(path)
+    }
+}
+
+// Nodes are kept in a plain vector, which is smaller than the hash map they
+// came from and is all `restore` needs to rebuild it.
+#[derive(Debug, Clone)]
+pub struct TopologySnapshot {
+    nodes: Vec<TopologyNode>,
+}
+
+pub struct TopologyIndex<'a> {
+    topo: &'a Topology,
+    adjacency: HashMap<(NodeId, IfaceIndex, NodeId), IfaceIndex>,
+}
+
+impl<'a> TopologyIndex<'a> {
+    pub fn topology(&self) -> &'a Topology {
+        self.topo
+    }
+
+    /// Same as `Topology::get_adjacent_interface`, but returns `None` instead
+    /// of panicking when the node or interface doesn't exist.
+    pub fn get_adjacent_interface(&self, from_node: NodeId,
+                                  via_if: IfaceIndex,
+                                  to_node: NodeId) -> Option<IfaceIndex> {
+        self.adjacency.get(&(from_node, via_if, to_node)).copied()
+    }
+}
+
+// `Ok` for a non-empty result, `NoRoute` otherwise.
+fn non_empty(paths: Vec<Path>) -> Result<Vec<Path>, PathError> {
+    if paths.is_empty() {
+        Err(PathError::NoRoute)
+    } else {
+        Ok(paths)
+    }
+}
+
+impl Default for Topology {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::collections::BTreeMap;
+
+    // A(1) -- (1)B(2) -- (1)C
+    fn create_line_topology() -> Topology {
+        fixtures::line_topology(3)
+    }
+
+    // A(1) -- (1)B(2) -- (1)C(2) -- Internet
+    fn create_line_topology_with_internet() -> Topology {
+        fixtures::line_with_gateway(3)
+    }
+
+    // Internet -- (2)A(1) -- (1)B(2) -- (1)C(2) -- Internet
+    fn create_line_topology_with_internet_2() -> Topology {
+        let mut topo = create_line_topology_with_internet();
+        let if_a_2 = Interface::new(IfaceIndex(2), InterfaceType::Internet, vec![]);
+        let node_a = topo.get_node_mut(NodeId(0xA));
+
+        node_a.add_iface(if_a_2);
+
+        topo
     }
 
     #[test]
@@ -398,14 +1871,9 @@ mod tests {
         let n_c = NodeId(0xC);
 
         let topo = create_line_topology();
-        let mut path = Path::new();
-        let mut paths: Vec<Path> = Vec::new();
-
-        topo.find_path(n_a,
-                       topo.get_local_app_iface_id(n_a).unwrap(),
-                       n_c,
-                       topo.get_local_app_iface_id(n_c).unwrap(),
-                       &mut path, &mut paths);
+        let paths = topo.find_paths_between(n_a, n_c).unwrap();
+        assert_eq!(paths.len(), 1);
+        check_paths_in_topology(&topo, paths, n_a, IfaceIndex(0), n_c, IfaceIndex(0));
     }
 
     // Internet -- (1)A(2) -- (1)B(4) -- (1)C(2) -- Internet
@@ -414,96 +1882,7 @@ mod tests {
     //                        (2)(3)(4)     (2)
     //                D(1) -- (1)E(5) -- (1)F
     fn create_big_topology() -> Topology {
-        let n_a = NodeId(0xA);
-        let n_b = NodeId(0xB);
-        let n_c = NodeId(0xC);
-        let n_d = NodeId(0xD);
-        let n_e = NodeId(0xE);
-        let n_f = NodeId(0xF);
-
-        let if_0 = IfaceIndex(0);
-        let if_1 = IfaceIndex(1);
-        let if_2 = IfaceIndex(2);
-        let if_3 = IfaceIndex(3);
-        let if_4 = IfaceIndex(4);
-        let if_5 = IfaceIndex(5);
-
-        let if_a_a = Interface::new(if_0, InterfaceType::LocalApp, vec![]);
-        let if_a_1 = Interface::new(if_1, InterfaceType::Internet, vec![]);
-        let if_a_2 = Interface::new(if_2, InterfaceType::LocalNet, vec![(n_b, if_1)]);
-
-        let if_b_a = Interface::new(if_0, InterfaceType::LocalApp, vec![]);
-        let if_b_1 = Interface::new(if_1, InterfaceType::LocalNet, vec![(n_a, if_2)]);
-        let if_b_2 = Interface::new(if_2, InterfaceType::LocalNet, vec![(n_e, if_2)]);
-        let if_b_3 = Interface::new(if_3, InterfaceType::LocalNet, vec![(n_e, if_3)]);
-        let if_b_4 = Interface::new(if_4, InterfaceType::LocalNet, vec![(n_c, if_1)]);
-
-        let if_c_a = Interface::new(if_0, InterfaceType::LocalApp, vec![]);
-        let if_c_1 = Interface::new(if_1, InterfaceType::LocalNet, vec![(n_b, if_4)]);
-        let if_c_2 = Interface::new(if_2, InterfaceType::Internet, vec![]);
-        let if_c_3 = Interface::new(if_3, InterfaceType::LocalNet, vec![(n_e, if_4)]);
-        let if_c_4 = Interface::new(if_4, InterfaceType::LocalNet, vec![(n_f, if_2)]);
-
-        let if_d_a = Interface::new(if_0, InterfaceType::LocalApp, vec![]);
-        let if_d_1 = Interface::new(if_1, InterfaceType::LocalNet, vec![(n_e, if_1)]);
-
-        let if_e_a = Interface::new(if_0, InterfaceType::LocalApp, vec![]);
-        let if_e_1 = Interface::new(if_1, InterfaceType::LocalNet, vec![(n_d, if_1)]);
-        let if_e_2 = Interface::new(if_2, InterfaceType::LocalNet, vec![(n_b, if_2)]);
-        let if_e_3 = Interface::new(if_3, InterfaceType::LocalNet, vec![(n_b, if_3)]);
-        let if_e_4 = Interface::new(if_4, InterfaceType::LocalNet, vec![(n_c, if_3)]);
-        let if_e_5 = Interface::new(if_5, InterfaceType::LocalNet, vec![(n_f, if_1)]);
-
-        let if_f_a = Interface::new(if_0, InterfaceType::LocalApp, vec![]);
-        let if_f_1 = Interface::new(if_1, InterfaceType::LocalNet, vec![(n_e, if_5)]);
-        let if_f_2 = Interface::new(if_2, InterfaceType::LocalNet, vec![(n_c, if_4)]);
-
-        let mut node_a = TopologyNode::new(n_a);
-        let mut node_b = TopologyNode::new(n_b);
-        let mut node_c = TopologyNode::new(n_c);
-        let mut node_d = TopologyNode::new(n_d);
-        let mut node_e = TopologyNode::new(n_e);
-        let mut node_f = TopologyNode::new(n_f);
-
-        node_a.add_iface(if_a_a);
-        node_a.add_iface(if_a_1);
-        node_a.add_iface(if_a_2);
-
-        node_b.add_iface(if_b_a);
-        node_b.add_iface(if_b_1);
-        node_b.add_iface(if_b_2);
-        node_b.add_iface(if_b_3);
-        node_b.add_iface(if_b_4);
-
-        node_c.add_iface(if_c_a);
-        node_c.add_iface(if_c_1);
-        node_c.add_iface(if_c_2);
-        node_c.add_iface(if_c_3);
-        node_c.add_iface(if_c_4);
-
-        node_d.add_iface(if_d_a);
-        node_d.add_iface(if_d_1);
-
-        node_e.add_iface(if_e_a);
-        node_e.add_iface(if_e_1);
-        node_e.add_iface(if_e_2);
-        node_e.add_iface(if_e_3);
-        node_e.add_iface(if_e_4);
-        node_e.add_iface(if_e_5);
-
-        node_f.add_iface(if_f_a);
-        node_f.add_iface(if_f_1);
-        node_f.add_iface(if_f_2);
-
-        let mut topo = Topology::new();
-        topo.add_node(node_a);
-        topo.add_node(node_b);
-        topo.add_node(node_c);
-        topo.add_node(node_d);
-        topo.add_node(node_e);
-        topo.add_node(node_f);
-
-        topo
+        fixtures::sample_mesh()
     }
 
     #[test]
@@ -515,7 +1894,7 @@ mod tests {
         let mut path = Path::new();
         let mut paths: Vec<Path> = Vec::new();
 
-        let start_if_id = topo.get_local_app_iface_id(n_d).unwrap();
+        let start_if_id = IfaceIndex(0);
         let finish_if_id = topo.get_internet_iface_id(n_c).unwrap();
 
         topo.find_path(n_d, start_if_id,
@@ -571,7 +1950,7 @@ mod tests {
                 let mut path = Path::new();
                 let mut paths: Vec<Path> = Vec::new();
 
-                let start_if_id = topo.get_local_app_iface_id(*node_id).unwrap();
+                let start_if_id = IfaceIndex(0);
                 let finish_if_id = topo.get_internet_iface_id(gateway_id).unwrap();
                 topo.find_path(*node_id,
                                start_if_id,
@@ -583,4 +1962,2121 @@ mod tests {
             }
         }
     }
+
+    // A(1) -- (1)B(2) -- (1)C(2) -- (1)D(2) -- (2)A
+    fn create_ring_topology() -> Topology {
+        let ids = [NodeId(0xA), NodeId(0xB), NodeId(0xC), NodeId(0xD)];
+
+        let if_0 = IfaceIndex(0);
+        let if_1 = IfaceIndex(1);
+        let if_2 = IfaceIndex(2);
+
+        let mut topo = Topology::new();
+        for (i, &id) in ids.iter().enumerate() {
+            let prev_id = ids[(i + ids.len() - 1) % ids.len()];
+            let next_id = ids[(i + 1) % ids.len()];
+
+            let mut node = TopologyNode::new(id);
+            node.add_iface(Interface::new(if_0, InterfaceType::LocalApp, vec![]));
+            node.add_iface(Interface::new(if_1, InterfaceType::LocalNet, vec![(prev_id, if_2)]));
+            node.add_iface(Interface::new(if_2, InterfaceType::LocalNet, vec![(next_id, if_1)]));
+            topo.add_node(node);
+        }
+        topo
+    }
+
+    #[test]
+    fn connectivity() {
+        let mut topo = create_line_topology();
+        assert!(topo.is_connected());
+
+        topo.add_node(TopologyNode::new(NodeId(0xD)));
+        assert!(!topo.is_connected());
+        assert!(Topology::new().is_connected());
+    }
+
+    #[test]
+    fn k_connectivity() {
+        let line = create_line_topology();
+        assert!(line.is_k_connected(0));
+        assert!(line.is_k_connected(1));
+        assert!(!line.is_k_connected(2));
+
+        let ring = create_ring_topology();
+        assert!(ring.is_k_connected(2));
+        assert!(!ring.is_k_connected(3));
+
+        // E is the only way out of D
+        let big = create_big_topology();
+        assert!(big.is_k_connected(1));
+        assert!(!big.is_k_connected(2));
+    }
+
+    fn path_ids(path: &Path) -> Vec<NodeId> {
+        path.nodes.iter().map(|x| x.id).collect()
+    }
+
+    #[test]
+    fn backup_path_node_disjoint() {
+        let (n_a, n_b, n_c, n_d) = (NodeId(0xA), NodeId(0xB), NodeId(0xC), NodeId(0xD));
+        let topo = create_ring_topology();
+
+        let primary = topo.shortest_path(n_a, n_c).unwrap();
+        let backup = topo.backup_path(n_a, n_c, &primary).unwrap();
+
+        let (primary_via, backup_via) = (path_ids(&primary)[1], path_ids(&backup)[1]);
+        assert_ne!(primary_via, backup_via);
+        assert!([n_b, n_d].contains(&primary_via));
+        assert!([n_b, n_d].contains(&backup_via));
+        check_paths_in_topology(&topo, vec![backup], n_a, Default::default(), n_c, Default::default());
+    }
+
+    #[test]
+    fn backup_path_least_overlap() {
+        let (n_c, n_d, n_e) = (NodeId(0xC), NodeId(0xD), NodeId(0xE));
+        let topo = create_big_topology();
+
+        let primary = topo.shortest_path(n_d, n_c).unwrap();
+        assert_eq!(path_ids(&primary), vec![n_d, n_e, n_c]);
+
+        // every route out of D crosses E, but the E -- C link can be avoided
+        let backup = topo.backup_path(n_d, n_c, &primary).unwrap();
+        let ids = path_ids(&backup);
+        assert_eq!(ids.len(), 4);
+        assert_eq!(&ids[..2], &[n_d, n_e]);
+        assert_eq!(ids[3], n_c);
+    }
+
+    #[test]
+    fn backup_path_none() {
+        let (n_a, n_c) = (NodeId(0xA), NodeId(0xC));
+        let topo = create_line_topology();
+
+        let primary = topo.shortest_path(n_a, n_c).unwrap();
+        assert!(topo.backup_path(n_a, n_c, &primary).is_none());
+    }
+
+    #[test]
+    fn node_interfaces() {
+        let topo = create_line_topology_with_internet();
+        let node_c = topo.get_node(NodeId(0xC));
+
+        assert_eq!(node_c.id(), NodeId(0xC));
+
+        let ifaces: Vec<&Interface> = node_c.interfaces().collect();
+        assert_eq!(ifaces.len(), 3);
+        assert_eq!(ifaces.iter().map(|x| x.index()).collect::<Vec<_>>(),
+                   vec![IfaceIndex(0), IfaceIndex(1), IfaceIndex(2)]);
+        assert_eq!(ifaces[0].iface_type(), InterfaceType::LocalApp);
+        assert_eq!(ifaces[1].iface_type(), InterfaceType::LocalNet);
+        assert_eq!(ifaces[2].iface_type(), InterfaceType::Internet);
+        assert_eq!(ifaces[1].neighbors(), &[(NodeId(0xB), IfaceIndex(2))]);
+        assert!(ifaces[2].neighbors().is_empty());
+    }
+
+    #[test]
+    fn node_neighbors() {
+        let (n_a, n_b, n_c) = (NodeId(0xA), NodeId(0xB), NodeId(0xC));
+        let (if_1, if_2) = (IfaceIndex(1), IfaceIndex(2));
+        let topo = create_line_topology_with_internet();
+
+        assert_eq!(topo.neighbors(n_a).collect::<Vec<_>>(), vec![(if_1, n_b, if_1)]);
+        assert_eq!(topo.neighbors(n_b).collect::<Vec<_>>(), vec![(if_1, n_a, if_1), (if_2, n_c, if_1)]);
+        assert_eq!(topo.neighbors(n_c).collect::<Vec<_>>(), vec![(if_1, n_b, if_2)]);
+        assert_eq!(topo.neighbors(NodeId(0xF)).count(), 0);
+    }
+
+    #[test]
+    fn adjacency_index() {
+        let topo = create_big_topology();
+        let index = topo.build_index();
+
+        for (&from_id, node) in topo.nodes.iter() {
+            for &via_if in node.ifaces.keys() {
+                for &to_id in topo.nodes.keys() {
+                    assert_eq!(index.get_adjacent_interface(from_id, via_if, to_id),
+                               topo.get_adjacent_interface(from_id, via_if, to_id));
+                }
+            }
+        }
+        assert_eq!(index.get_adjacent_interface(NodeId(0x1), IfaceIndex(1), NodeId(0xA)), None);
+    }
+
+    #[test]
+    fn distances() {
+        let (n_a, n_b, n_c, n_d, n_e, n_f) =
+            (NodeId(0xA), NodeId(0xB), NodeId(0xC), NodeId(0xD), NodeId(0xE), NodeId(0xF));
+        let mut topo = create_big_topology();
+
+        let hops = topo.distances_from(n_d);
+        assert_eq!(hops, HashMap::from([(n_d, 0), (n_e, 1), (n_b, 2), (n_c, 2), (n_f, 2), (n_a, 3)]));
+
+        // make E -- C expensive so that D reaches C around it
+        topo.get_node_mut(n_e).ifaces.get_mut(&IfaceIndex(4)).unwrap().set_cost(10);
+        let costs = topo.distances_from_weighted(n_d);
+        assert_eq!(costs, HashMap::from([(n_d, 0), (n_e, 1), (n_b, 2), (n_c, 3), (n_f, 2), (n_a, 3)]));
+
+        topo.add_node(TopologyNode::new(NodeId(0x1)));
+        assert!(!topo.distances_from(n_d).contains_key(&NodeId(0x1)));
+        assert!(topo.distances_from(NodeId(0x2)).is_empty());
+    }
+
+    #[test]
+    fn snapshot_restore() {
+        let mut topo = create_big_topology();
+        let snap = topo.snapshot();
+
+        topo.add_node(TopologyNode::new(NodeId(0x1)));
+        topo.get_node_mut(NodeId(0xA)).ifaces.remove(&IfaceIndex(2));
+        topo.get_node_mut(NodeId(0xE)).ifaces.get_mut(&IfaceIndex(4)).unwrap().set_cost(10);
+        assert_ne!(topo, create_big_topology());
+
+        topo.restore(snap);
+        assert_eq!(topo, create_big_topology());
+    }
+
+    #[test]
+    fn path_describe() {
+        let topo = create_line_topology();
+        let path = topo.shortest_path(NodeId(0xA), NodeId(0xC)).unwrap();
+
+        assert_eq!(path.describe(),
+                   "A exits via iface 1 -> B enters via iface 1\n\
+                    B exits via iface 2 -> C enters via iface 1");
+        assert_eq!(format!("{path}"), "Path: (0)A(1) => (1)B(2) => (1)C(0)");
+        assert_eq!(Path::new().describe(), "");
+    }
+
+    #[test]
+    fn path_total_cost() {
+        let n_a = NodeId(0xA);
+        let n_c = NodeId(0xC);
+
+        let topo = create_line_topology();
+        let paths = topo.find_paths_between(n_a, n_c).unwrap();
+
+        assert_eq!(paths.len(), 1);
+        assert_eq!(paths[0].total_cost(&topo), 2);
+        assert_eq!(Path::new().total_cost(&topo), 0);
+    }
+
+    #[test]
+    fn components() {
+        let mut topo = create_line_topology();
+        assert_eq!(topo.connected_components(), vec![vec![NodeId(0xA), NodeId(0xB), NodeId(0xC)]]);
+
+        topo.add_node(TopologyNode::new(NodeId(0x1)));
+        assert_eq!(topo.connected_components(),
+                   vec![vec![NodeId(0x1)], vec![NodeId(0xA), NodeId(0xB), NodeId(0xC)]]);
+    }
+
+    #[test]
+    fn center_and_periphery() {
+        let (n_a, n_b, n_c, n_d, n_e, n_f) =
+            (NodeId(0xA), NodeId(0xB), NodeId(0xC), NodeId(0xD), NodeId(0xE), NodeId(0xF));
+
+        let line = create_line_topology();
+        assert_eq!(line.eccentricity(n_a), Some(2));
+        assert_eq!(line.eccentricity(n_b), Some(1));
+        assert_eq!(line.eccentricity(NodeId(0x1)), None);
+        assert_eq!(line.center(), vec![n_b]);
+        assert_eq!(line.periphery(), vec![n_a, n_c]);
+
+        let big = create_big_topology();
+        assert_eq!(big.all_pairs_shortest_hops()[&n_d][&n_a], 3);
+        assert_eq!(big.center(), vec![n_b, n_c, n_e]);
+        assert_eq!(big.periphery(), vec![n_a, n_d, n_f]);
+
+        // every component contributes its own center and periphery
+        let mut two_islands = create_line_topology();
+        two_islands.add_node(TopologyNode::new(NodeId(0x1)));
+        assert_eq!(two_islands.center(), vec![NodeId(0x1), n_b]);
+        assert_eq!(two_islands.periphery(), vec![NodeId(0x1), n_a, n_c]);
+    }
+
+    #[test]
+    fn find_paths_local_only() {
+        let (n_a, n_b, n_c) = (NodeId(0xA), NodeId(0xB), NodeId(0xC));
+        let (if_0, if_3) = (IfaceIndex(0), IfaceIndex(3));
+
+        // A(1) -- (1)B(2) -- (1)C plus A(3) and C(3) reaching each other
+        // over the Internet
+        let mut topo = create_line_topology();
+        topo.get_node_mut(n_a).add_iface(Interface::new(if_3, InterfaceType::Internet, vec![(n_c, if_3)]));
+        topo.get_node_mut(n_c).add_iface(Interface::new(if_3, InterfaceType::Internet, vec![(n_a, if_3)]));
+
+        let paths = topo.find_paths_local_only(n_a, if_0, n_c, if_0).unwrap();
+        assert_eq!(paths.len(), 1);
+        assert_eq!(path_ids(&paths[0]), vec![n_a, n_b, n_c]);
+        check_paths_in_topology(&topo, paths, n_a, if_0, n_c, if_0);
+
+        // an Internet finish interface is fine, it isn't transited
+        let paths = topo.find_paths_local_only(n_a, if_0, n_c, if_3).unwrap();
+        assert_eq!(paths.len(), 1);
+        assert_eq!(paths[0].nodes.back().unwrap().forward_if_id, if_3);
+
+        // the Internet shortcut is found second, but preferred
+        let all = topo.find_paths(n_a, if_0, n_c, if_0).unwrap();
+        assert_eq!(all.iter().map(path_ids).collect::<Vec<_>>(), vec![vec![n_a, n_b, n_c], vec![n_a, n_c]]);
+        let paths = topo.find_paths_prefer_internet(n_a, if_0, n_c, if_0).unwrap();
+        assert_eq!(paths, vec![all[1].clone(), all[0].clone()]);
+        assert_eq!(topo.find_paths_prefer_internet(n_a, if_0, NodeId(0x1), if_0), Err(PathError::FinishNotFound(NodeId(0x1))));
+    }
+
+    #[test]
+    fn paths_best_first() {
+        let n_c = NodeId(0xC);
+        let n_d = NodeId(0xD);
+
+        let topo = create_big_topology();
+        let start_if_id = IfaceIndex(0);
+        let finish_if_id = topo.get_internet_iface_id(n_c).unwrap();
+
+        let paths: Vec<Path> = topo.paths_best_first(n_d, start_if_id, n_c, finish_if_id).collect();
+        assert_eq!(paths[0].nodes.len(), 3);
+        assert!(paths.windows(2).all(|x| x[0].nodes.len() <= x[1].nodes.len()));
+        for (i, path) in paths.iter().enumerate() {
+            assert!(!paths[i + 1..].contains(path));
+        }
+
+        let first_two: Vec<Path> = topo.paths_best_first(n_d, start_if_id, n_c, finish_if_id).take(2).collect();
+        assert_eq!(first_two[..], paths[..2]);
+
+        check_paths_in_topology(&topo, paths, n_d, start_if_id, n_c, finish_if_id);
+    }
+
+    #[test]
+    fn duplicate_neighbors() {
+        let (n_a, n_b) = (NodeId(0xA), NodeId(0xB));
+        let if_1 = IfaceIndex(1);
+
+        let mut topo = create_line_topology();
+        assert!(topo.duplicate_neighbors().is_empty());
+        assert_eq!(topo.deduplicate_neighbors(), 0);
+
+        let neighbors = &mut topo.get_node_mut(n_a).ifaces.get_mut(&if_1).unwrap().neighbors;
+        neighbors.push((n_b, if_1));
+        neighbors.push((n_b, if_1));
+        assert_eq!(topo.duplicate_neighbors(), vec![(n_a, if_1, n_b, if_1), (n_a, if_1, n_b, if_1)]);
+
+        assert_eq!(topo.deduplicate_neighbors(), 2);
+        assert!(topo.duplicate_neighbors().is_empty());
+        assert_eq!(topo, create_line_topology());
+    }
+
+    #[test]
+    fn connect_nodes() {
+        let (n_a, n_b, n_c) = (NodeId(0xA), NodeId(0xB), NodeId(0xC));
+        let (if_0, if_1, if_2) = (IfaceIndex(0), IfaceIndex(1), IfaceIndex(2));
+
+        let mut topo = Topology::new();
+        for id in [n_a, n_b, n_c] {
+            let mut node = TopologyNode::new(id);
+            node.add_iface(Interface::new(if_0, InterfaceType::LocalApp, vec![]));
+            topo.add_node(node);
+        }
+
+        assert_eq!(topo.connect(n_a, if_1, n_b, if_1), Ok(()));
+        assert_eq!(topo.connect(n_b, if_2, n_c, if_1), Ok(()));
+        assert_eq!(topo, create_line_topology());
+
+        assert_eq!(topo.connect(n_a, if_1, n_c, if_2), Err(TopologyError::InterfaceExists(n_a, if_1)));
+        assert_eq!(topo.connect(n_a, if_2, NodeId(0x1), if_1), Err(TopologyError::NodeNotFound(NodeId(0x1))));
+        assert_eq!(topo.connect(n_a, if_2, n_a, if_2), Err(TopologyError::SelfLoop(n_a, if_2)));
+        assert_eq!(topo, create_line_topology());
+    }
+
+    #[test]
+    fn self_loops() {
+        let n_a = NodeId(0xA);
+        let if_2 = IfaceIndex(2);
+
+        let mut topo = create_line_topology();
+        assert!(topo.self_loops().is_empty());
+
+        let looped = Interface::new(if_2, InterfaceType::LocalNet, vec![(n_a, if_2)]);
+        assert_eq!(topo.get_node_mut(n_a).try_add_iface(looped.clone()), Err(TopologyError::SelfLoop(n_a, if_2)));
+        assert_eq!(topo.get_node_mut(n_a).try_add_iface(Interface::new(IfaceIndex(1), InterfaceType::LocalNet, vec![])),
+                   Err(TopologyError::InterfaceExists(n_a, IfaceIndex(1))));
+
+        topo.get_node_mut(n_a).add_iface(looped);
+        assert_eq!(topo.self_loops(), vec![(n_a, if_2)]);
+
+        // the DFS must still terminate and ignore the loop
+        let paths = topo.find_paths(n_a, IfaceIndex(0), NodeId(0xC), IfaceIndex(0)).unwrap();
+        assert_eq!(paths.len(), 1);
+        assert_eq!(path_ids(&paths[0]), vec![n_a, NodeId(0xB), NodeId(0xC)]);
+    }
+
+    #[test]
+    fn type_costs() {
+        let (n_b, n_c, n_d, n_e) = (NodeId(0xB), NodeId(0xC), NodeId(0xD), NodeId(0xE));
+        let mut topo = create_big_topology();
+
+        topo.get_node_mut(n_e).ifaces.get_mut(&IfaceIndex(4)).unwrap().set_cost(20);
+        topo.set_type_costs(5, 100);
+
+        assert_eq!(topo.get_node(n_c).ifaces[&IfaceIndex(1)].cost(), 5);
+        assert_eq!(topo.get_node(n_c).ifaces[&IfaceIndex(2)].cost(), 100);
+        assert_eq!(topo.get_node(n_c).ifaces[&IfaceIndex(0)].cost(), 1);
+        assert_eq!(topo.get_node(n_e).ifaces[&IfaceIndex(4)].cost(), 20);
+
+        // E -> C directly costs 20, around it over B costs 10
+        let path = topo.shortest_path_weighted(n_d, n_c).unwrap();
+        assert_eq!(path_ids(&path), vec![n_d, n_e, n_b, n_c]);
+        assert_eq!(path.total_cost(&topo), 15);
+
+        // A(1) -- (1)B(2) -- (1)C plus A(3) and C(3) reaching each other
+        // over the Internet
+        let (n_a, if_3) = (NodeId(0xA), IfaceIndex(3));
+        let mut line = create_line_topology();
+        line.get_node_mut(n_a).add_iface(Interface::new(if_3, InterfaceType::Internet, vec![(n_c, if_3)]));
+        line.get_node_mut(n_c).add_iface(Interface::new(if_3, InterfaceType::Internet, vec![(n_a, if_3)]));
+
+        line.set_type_costs(1, 5);
+        assert_eq!(path_ids(&line.shortest_path_weighted(n_a, n_c).unwrap()), vec![n_a, n_b, n_c]);
+        line.set_type_costs(3, 5);
+        let path = line.shortest_path_weighted(n_a, n_c).unwrap();
+        assert_eq!(path_ids(&path), vec![n_a, n_c]);
+        assert_eq!(path.nodes[0].forward_if_id, if_3);
+        assert!(line.is_valid_path(&path));
+    }
+
+    #[test]
+    fn find_gateway_interfaces() {
+        let (n_a, n_c) = (NodeId(0xA), NodeId(0xC));
+        let (if_2, if_3) = (IfaceIndex(2), IfaceIndex(3));
+
+        assert!(create_line_topology().gateway_interfaces().is_empty());
+
+        let mut topo = create_line_topology_with_internet_2();
+        assert_eq!(topo.gateway_interfaces(), vec![(n_a, if_2), (n_c, if_2)]);
+
+        topo.get_node_mut(n_a).add_iface(Interface::new(if_3, InterfaceType::Internet, vec![]));
+        assert_eq!(topo.gateway_interfaces(), vec![(n_a, if_2), (n_a, if_3), (n_c, if_2)]);
+    }
+
+    #[test]
+    fn path_validity() {
+        let (n_a, n_b, n_c) = (NodeId(0xA), NodeId(0xB), NodeId(0xC));
+        let mut topo = create_line_topology();
+
+        let path = topo.shortest_path(n_a, n_c).unwrap();
+        assert!(topo.is_valid_path(&path));
+        assert!(topo.is_valid_path(&topo.shortest_path(n_a, n_a).unwrap()));
+        assert!(!topo.is_valid_path(&Path::new()));
+
+        let mut wrong_iface = path.clone();
+        wrong_iface.nodes[1].reverse_if_id = IfaceIndex(2);
+        assert!(!topo.is_valid_path(&wrong_iface));
+
+        let mut looped = path.clone();
+        looped.nodes.push_back(looped.nodes[1].clone());
+        assert!(!topo.is_valid_path(&looped));
+
+        // stale after B disappears
+        topo.nodes.remove(&n_b);
+        assert!(!topo.is_valid_path(&path));
+    }
+
+    #[test]
+    fn count_paths() {
+        let (n_c, n_d) = (NodeId(0xC), NodeId(0xD));
+        let topo = create_big_topology();
+
+        // D-E-C, D-E-F-C and D-E-B-C over either of the parallel B -- E links
+        assert_eq!(topo.count_paths(n_d, n_c), 4);
+        assert_eq!(topo.count_paths(n_d, n_c),
+                   topo.paths_best_first(n_d, IfaceIndex(0), n_c, IfaceIndex(0)).count());
+        assert_eq!(topo.count_paths(n_d, n_d), 1);
+        assert_eq!(topo.count_paths(n_d, NodeId(0x1)), 0);
+    }
+
+    #[test]
+    fn mermaid() {
+        let topo = create_line_topology_with_internet();
+
+        let expected = [
+            "graph LR",
+            "    nA[A]",
+            "    nB[B]",
+            "    nC[C]",
+            "    nA ---|\"1:1\"| nB",
+            "    nB ---|\"2:1\"| nC",
+            "    inet_C_2([Internet])",
+            "    nC ---|\"2\"| inet_C_2",
+            "",
+        ];
+        assert_eq!(topo.to_mermaid(), expected.join("\n"));
+    }
+
+    #[test]
+    fn shortest_path_tree() {
+        let (n_c, n_d, n_e) = (NodeId(0xC), NodeId(0xD), NodeId(0xE));
+        let mut topo = create_big_topology();
+        topo.add_node(TopologyNode::new(NodeId(0x1)));
+
+        let tree = topo.shortest_path_tree(n_d);
+        assert_eq!(tree.nodes.len(), 6);
+        assert!(!tree.nodes.contains_key(&NodeId(0x1)));
+        assert!(tree.is_connected());
+        assert_eq!(tree.canonical_links().len(), 5);
+        assert_eq!(tree.count_paths(n_d, n_c), 1);
+        assert_eq!(tree.distances_from(n_d), topo.distances_from(n_d));
+
+        // interfaces keep their indices, gateways keep their egress
+        assert_eq!(tree.get_adjacent_interface(n_e, IfaceIndex(4), n_c), Some(IfaceIndex(3)));
+        assert_eq!(tree.gateway_interfaces(), vec![(NodeId(0xA), IfaceIndex(1)), (n_c, IfaceIndex(2))]);
+    }
+
+    #[test]
+    fn filter_nodes() {
+        let topo = create_big_topology();
+
+        let gateways = topo.nodes_where(|node| node.interfaces().any(|x| x.iface_type() == InterfaceType::Internet));
+        let mut expected = topo.find_internet_gateway();
+        expected.sort();
+        assert_eq!(gateways, expected);
+
+        let well_connected_gateways = topo.nodes_where(|node| {
+            node.interfaces().any(|x| x.iface_type() == InterfaceType::Internet)
+                && node.interfaces().map(|x| x.neighbors().len()).sum::<usize>() >= 3
+        });
+        assert_eq!(well_connected_gateways, vec![NodeId(0xC)]);
+        assert!(topo.nodes_where(|_| false).is_empty());
+    }
+
+    #[test]
+    fn labels() {
+        let (n_a, n_b) = (NodeId(0xA), NodeId(0xB));
+        let if_1 = IfaceIndex(1);
+
+        let mut topo = create_line_topology();
+        topo.get_node_mut(n_a).set_label("core-sw-1");
+        topo.get_node_mut(n_a).ifaces.get_mut(&if_1).unwrap().set_label("eth0");
+
+        let node_a = topo.get_node(n_a);
+        assert_eq!(node_a.label(), Some("core-sw-1"));
+        assert_eq!(format!("{node_a}"), "A (core-sw-1)");
+        assert_eq!(format!("{}", node_a.ifaces[&if_1]), "1 (eth0)");
+        assert_eq!(format!("{}", topo.get_node(n_b)), "B");
+        assert_eq!(topo.get_node(n_b).label(), None);
+
+        // metadata only
+        assert_eq!(topo, create_line_topology());
+        assert_eq!(topo.shortest_path(n_a, NodeId(0xC)), create_line_topology().shortest_path(n_a, NodeId(0xC)));
+
+        let dot = topo.to_dot();
+        assert!(dot.contains("nA [label=\"A\\ncore-sw-1\"];"));
+        assert!(dot.contains("nA -- nB [taillabel=\"1 (eth0)\", headlabel=\"1\"];"));
+
+        // a trailing backslash mustn't eat the closing quote
+        topo.get_node_mut(n_a).set_label(r#"rack "7" \"#);
+        topo.get_node_mut(n_a).ifaces.get_mut(&if_1).unwrap().set_label(r"C:\");
+        let dot = topo.to_dot();
+        assert!(dot.contains(r#"nA [label="A\nrack \"7\" \\"];"#));
+        assert!(dot.contains(r#"nA -- nB [taillabel="1 (C:\\)", headlabel="1"];"#));
+    }
+
+    #[test]
+    fn dot() {
+        let topo = create_line_topology_with_internet();
+
+        let expected = [
+            "graph topology {",
+            "    nA [label=\"A\"];",
+            "    nB [label=\"B\"];",
+            "    nC [label=\"C\"];",
+            "    nA -- nB [taillabel=\"1\", headlabel=\"1\"];",
+            "    nB -- nC [taillabel=\"2\", headlabel=\"1\"];",
+            "    inet_C_2 [label=\"Internet\", shape=ellipse];",
+            "    nC -- inet_C_2 [taillabel=\"2\"];",
+            "}",
+            "",
+        ];
+        assert_eq!(topo.to_dot(), expected.join("\n"));
+    }
+
+    #[test]
+    fn common_transit() {
+        let (n_a, n_b, n_c, n_d, n_e) = (NodeId(0xA), NodeId(0xB), NodeId(0xC), NodeId(0xD), NodeId(0xE));
+        let topo = create_big_topology();
+
+        // D -> E -> B -> A and D -> E -> C
+        assert_eq!(topo.common_transit(n_d, n_a, n_c), vec![n_e]);
+        // E -> B -> A and E -> C
+        assert!(topo.common_transit(n_e, n_a, n_c).is_empty());
+        // endpoints are never transit: B is gw_a here
+        assert!(topo.common_transit(n_a, n_b, n_c).is_empty());
+        assert!(topo.common_transit(n_d, n_a, NodeId(0x1)).is_empty());
+    }
+
+    #[test]
+    fn free_ifaces() {
+        let n_b = NodeId(0xB);
+        let mut topo = create_line_topology();
+
+        assert_eq!(topo.used_ifaces(n_b), vec![IfaceIndex(0), IfaceIndex(1), IfaceIndex(2)]);
+        assert_eq!(topo.next_free_iface(n_b), Some(IfaceIndex(3)));
+
+        topo.get_node_mut(n_b).ifaces.remove(&IfaceIndex(1));
+        assert_eq!(topo.next_free_iface(n_b), Some(IfaceIndex(1)));
+
+        for if_id in 0..=u8::MAX {
+            topo.get_node_mut(n_b).add_iface(Interface::new(IfaceIndex(if_id), InterfaceType::LocalNet, vec![]));
+        }
+        assert_eq!(topo.used_ifaces(n_b).len(), 256);
+        assert_eq!(topo.next_free_iface(n_b), None);
+
+        assert!(topo.used_ifaces(NodeId(0x1)).is_empty());
+        assert_eq!(topo.next_free_iface(NodeId(0x1)), None);
+    }
+
+    #[test]
+    fn connect_nodes_auto() {
+        let (n_a, n_b, n_c) = (NodeId(0xA), NodeId(0xB), NodeId(0xC));
+        let (if_0, if_1, if_2) = (IfaceIndex(0), IfaceIndex(1), IfaceIndex(2));
+
+        let mut topo = Topology::new();
+        for id in [n_a, n_b, n_c] {
+            let mut node = TopologyNode::new(id);
+            node.add_iface(Interface::new(if_0, InterfaceType::LocalApp, vec![]));
+            topo.add_node(node);
+        }
+
+        assert_eq!(topo.connect_auto(n_a, n_b, InterfaceType::LocalNet), Ok((if_1, if_1)));
+        assert_eq!(topo.connect_auto(n_b, n_c, InterfaceType::LocalNet), Ok((if_2, if_1)));
+        assert_eq!(topo, create_line_topology());
+
+        assert_eq!(topo.connect_auto(n_a, n_a, InterfaceType::LocalNet), Err(TopologyError::SelfLoop(n_a, if_2)));
+        assert_eq!(topo.connect_auto(n_a, NodeId(0x1), InterfaceType::LocalNet), Err(TopologyError::NodeNotFound(NodeId(0x1))));
+
+        for if_id in 3..=u8::MAX {
+            topo.get_node_mut(n_c).add_iface(Interface::new(IfaceIndex(if_id), InterfaceType::LocalNet, vec![]));
+        }
+        topo.get_node_mut(n_c).add_iface(Interface::new(if_2, InterfaceType::LocalNet, vec![]));
+        assert_eq!(topo.connect_auto(n_a, n_c, InterfaceType::LocalNet), Err(TopologyError::InterfacesExhausted(n_c)));
+        assert_eq!(topo.used_ifaces(n_a), vec![if_0, if_1]);
+    }
+
+    #[test]
+    fn parallel_links() {
+        let (n_b, n_e) = (NodeId(0xB), NodeId(0xE));
+        let (if_2, if_3) = (IfaceIndex(2), IfaceIndex(3));
+
+        assert!(create_line_topology().parallel_links().is_empty());
+        assert_eq!(create_big_topology().parallel_links(), vec![(n_b, n_e, vec![(if_2, if_2), (if_3, if_3)])]);
+    }
+
+    #[test]
+    fn sample_path() {
+        let (n_a, n_b, n_c, n_d) = (NodeId(0xA), NodeId(0xB), NodeId(0xC), NodeId(0xD));
+        let mut topo = create_ring_topology();
+
+        // A -> D -> C costs 18, A -> B -> C costs 2
+        topo.get_node_mut(n_a).ifaces.get_mut(&IfaceIndex(1)).unwrap().set_cost(9);
+        topo.get_node_mut(n_d).ifaces.get_mut(&IfaceIndex(1)).unwrap().set_cost(9);
+
+        let mut via_b = 0;
+        for seed in 0..1000 {
+            let path = topo.sample_path(n_a, n_c, seed).unwrap();
+            assert_eq!(topo.sample_path(n_a, n_c, seed), Some(path.clone()));
+            if path_ids(&path)[1] == n_b {
+                via_b += 1;
+            }
+        }
+        // expected 900
+        assert!((850..950).contains(&via_b));
+
+        assert_eq!(create_line_topology().sample_path(n_a, NodeId(0x1), 0), None);
+        // nothing to route within one node
+        assert_eq!(topo.sample_path(n_a, n_a, 0), None);
+
+        // runs between the app interfaces, wherever they are
+        topo.get_node_mut(n_a).ifaces.remove(&IfaceIndex(0));
+        topo.get_node_mut(n_a).add_iface(Interface::new(IfaceIndex(3), InterfaceType::LocalApp, vec![]));
+        assert!(topo.sample_path(n_a, n_c, 0).unwrap().to_string().starts_with("Path: (3)A"));
+        assert_eq!(topo.sample_path(n_c, n_a, 0).unwrap().nodes.back().unwrap().forward_if_id, IfaceIndex(3));
+
+        // no app on C, so no app traffic to sample a route for
+        topo.get_node_mut(n_c).ifaces.remove(&IfaceIndex(0));
+        assert_eq!(topo.sample_path(n_a, n_c, 0), None);
+        assert_eq!(topo.sample_path(n_c, n_a, 0), None);
+    }
+
+    #[test]
+    fn component_report() {
+        let mut topo = create_line_topology_with_internet();
+        assert_eq!(topo.diameter(), Some(2));
+        assert_eq!(Topology::new().diameter(), None);
+
+        let mut island = TopologyNode::new(NodeId(0x1));
+        island.add_iface(Interface::new(IfaceIndex(0), InterfaceType::LocalApp, vec![]));
+        topo.add_node(island);
+
+        let report = topo.component_report();
+        assert_eq!(report.len(), 2);
+        assert_eq!(report[0], ComponentInfo {
+            nodes: vec![NodeId(0xA), NodeId(0xB), NodeId(0xC)],
+            diameter: 2,
+            has_gateway: true,
+        });
+        assert_eq!(report[0].size(), 3);
+        assert_eq!(report[1], ComponentInfo {
+            nodes: vec![NodeId(0x1)],
+            diameter: 0,
+            has_gateway: false,
+        });
+    }
+
+    #[test]
+    fn unique_path() {
+        let (n_a, n_c, n_d) = (NodeId(0xA), NodeId(0xC), NodeId(0xD));
+        let if_0 = IfaceIndex(0);
+
+        let mut line = create_line_topology();
+        let mut isolated = TopologyNode::new(NodeId(0x1));
+        isolated.add_iface(Interface::new(if_0, InterfaceType::LocalApp, vec![]));
+        line.add_node(isolated);
+        let path = line.unique_path(n_a, if_0, n_c, if_0).unwrap();
+        assert_eq!(path_ids(&path), vec![n_a, NodeId(0xB), n_c]);
+        assert_eq!(line.unique_path(n_a, if_0, NodeId(0x1), if_0), Err(PathError::NoRoute));
+        assert_eq!(line.find_paths_limited(n_a, if_0, n_a, if_0, 5), Err(PathError::NoRoute));
+        assert_eq!(line.unique_path(n_a, if_0, n_a, if_0), Err(PathError::NoRoute));
+        assert_eq!(line.paths_best_first(n_a, if_0, n_a, if_0).count(), 0);
+
+        let big = create_big_topology();
+        assert_eq!(big.find_paths_limited(n_d, if_0, n_c, if_0, 3).unwrap().len(), 3);
+        assert_eq!(big.unique_path(n_d, if_0, n_c, if_0), Err(PathError::Ambiguous(2)));
+        assert_eq!(PathError::Ambiguous(2).to_string(), "at least 2 paths found where one was expected");
+    }
+
+    #[test]
+    fn demands() {
+        let (n_a, n_b, n_c, n_d, n_e) = (NodeId(0xA), NodeId(0xB), NodeId(0xC), NodeId(0xD), NodeId(0xE));
+        let mut topo = create_big_topology();
+        topo.add_node(TopologyNode::new(NodeId(0x1)));
+        topo.get_node_mut(n_e).ifaces.get_mut(&IfaceIndex(4)).unwrap().set_bandwidth(35);
+
+        // D -> E -> C, D -> E -> B -> A, C -> B
+        let demands = [(n_d, n_c, 40), (n_d, n_a, 30), (n_c, n_b, 5), (n_d, NodeId(0x1), 100)];
+        let load = topo.apply_demands(&demands);
+
+        assert_eq!(load, HashMap::from([
+            ((n_d, IfaceIndex(1)), 70),
+            ((n_e, IfaceIndex(4)), 40),
+            ((n_e, IfaceIndex(2)), 30),
+            ((n_b, IfaceIndex(1)), 30),
+            ((n_c, IfaceIndex(1)), 5),
+        ]));
+        assert_eq!(topo.unroutable_demands(&demands), vec![(n_d, NodeId(0x1), 100)]);
+
+        let hot_spots: Vec<(NodeId, IfaceIndex)> = load.iter()
+                                                       .filter(|((id, if_id), load)| **load > topo.get_node(*id).ifaces[if_id].bandwidth())
+                                                       .map(|(key, _)| *key)
+                                                       .collect();
+        assert_eq!(hot_spots, vec![(n_e, IfaceIndex(4))]);
+    }
+
+    #[test]
+    fn longest_path() {
+        let (n_a, n_c, n_d) = (NodeId(0xA), NodeId(0xC), NodeId(0xD));
+        let topo = create_big_topology();
+
+        // D -> E -> B -> C would be 3 hops, A -> B -> E -> F -> C is 4
+        let path = topo.longest_path(n_a, n_c).unwrap();
+        assert_eq!(path.nodes.len(), 5);
+        assert!(topo.is_valid_path(&path));
+
+        let path = topo.longest_path(n_d, n_c).unwrap();
+        assert_eq!(path.nodes.len(), 4);
+
+        let path = topo.longest_path_within(n_a, n_c, 3).unwrap();
+        assert_eq!(path.nodes.len(), 4);
+        assert_eq!(topo.longest_path_within(n_a, n_c, 1), None);
+        assert_eq!(topo.longest_path(n_a, NodeId(0x1)), None);
+    }
+
+    #[test]
+    fn gateway_map() {
+        let (n_a, n_c, n_d, n_f) = (NodeId(0xA), NodeId(0xC), NodeId(0xD), NodeId(0xF));
+        let mut topo = create_big_topology();
+        topo.add_node(TopologyNode::new(NodeId(0x1)));
+
+        assert_eq!(topo.reachable_gateways(n_d), vec![n_a, n_c]);
+        let map = topo.gateway_map();
+        assert_eq!(map.len(), 7);
+        assert_eq!(map[&n_d], vec![(n_a, 3), (n_c, 2)]);
+        assert_eq!(map[&n_a], vec![(n_a, 0), (n_c, 2)]);
+        assert_eq!(map[&n_f], vec![(n_a, 3), (n_c, 1)]);
+        assert!(map[&NodeId(0x1)].is_empty());
+    }
+
+    #[test]
+    fn transit_policy() {
+        let (n_1, n_2, n_3, n_4) = (NodeId(0x1), NodeId(0x2), NodeId(0x3), NodeId(0x4));
+        let if_0 = IfaceIndex(0);
+        let mut topo = Topology::new();
+        for id in [n_1, n_2, n_3, n_4] {
+            let mut node = TopologyNode::new(id);
+            node.add_iface(Interface::new(if_0, InterfaceType::LocalApp, vec![]));
+            topo.add_node(node);
+        }
+
+        // 1 -> 2 -> 3 hairpins through 2's Internet interfaces, 1 -> 4 -> 3 doesn't
+        topo.connect_auto(n_1, n_2, InterfaceType::Internet).unwrap();
+        topo.connect_auto(n_2, n_3, InterfaceType::Internet).unwrap();
+        topo.connect_auto(n_1, n_4, InterfaceType::LocalNet).unwrap();
+        topo.connect_auto(n_4, n_3, InterfaceType::LocalNet).unwrap();
+
+        let default = topo.find_paths_with_policy(n_1, if_0, n_3, if_0, TransitPolicy::default()).unwrap();
+        assert_eq!(default, topo.find_paths(n_1, if_0, n_3, if_0).unwrap());
+        assert_eq!(default.len(), 2);
+
+        let paths = topo.find_paths_with_policy(n_1, if_0, n_3, if_0, TransitPolicy::no_internet_transit()).unwrap();
+        assert_eq!(paths.iter().map(path_ids).collect::<Vec<_>>(), vec![vec![n_1, n_4, n_3]]);
+
+        let policy = TransitPolicy::new().forbid(InterfaceType::LocalApp, InterfaceType::LocalNet);
+        let paths = topo.find_paths_with_policy(n_1, if_0, n_3, if_0, policy).unwrap();
+        assert_eq!(paths.iter().map(path_ids).collect::<Vec<_>>(), vec![vec![n_1, n_2, n_3]]);
+    }
+
+    #[test]
+    fn separating_vertices() {
+        let (n_a, n_b, n_c, n_d, n_e, n_f) = (NodeId(0xA), NodeId(0xB), NodeId(0xC), NodeId(0xD), NodeId(0xE), NodeId(0xF));
+        let mut topo = create_big_topology();
+
+        // A hangs off B and D off E
+        assert_eq!(topo.separating_vertices(n_a, n_d), vec![n_b, n_e]);
+        assert_eq!(topo.separating_vertices(n_a, n_f), vec![n_b]);
+        assert!(topo.separating_vertices(n_b, n_c).is_empty());
+        assert!(topo.separating_vertices(n_a, n_b).is_empty());
+
+        topo.add_node(TopologyNode::new(NodeId(0x1)));
+        assert!(topo.separating_vertices(n_a, NodeId(0x1)).is_empty());
+        assert!(topo.separating_vertices(n_a, NodeId(0x2)).is_empty());
+
+        let topo = create_line_topology();
+        assert_eq!(topo.separating_vertices(n_a, n_c), vec![n_b]);
+    }
+
+    #[test]
+    fn builder() {
+        let (n_a, n_b, n_c, n_d) = (NodeId(0xA), NodeId(0xB), NodeId(0xC), NodeId(0xD));
+        let (if_0, if_1, if_2) = (IfaceIndex(0), IfaceIndex(1), IfaceIndex(2));
+
+        let topo = TopologyBuilder::new().node(n_a)
+                                         .node(n_b)
+                                         .node(n_c)
+                                         .app(n_a, if_0)
+                                         .app(n_b, if_0)
+                                         .app(n_c, if_0)
+                                         .link(n_a, if_1, n_b, if_1)
+                                         .link(n_b, if_2, n_c, if_1)
+                                         .gateway(n_c, if_2)
+                                         .build();
+        assert_eq!(topo, Ok(create_line_topology_with_internet()));
+
+        let errors = TopologyBuilder::new().node(n_a)
+                                           .node(n_b)
+                                           .node(n_c)
+                                           .link(n_a, if_1, n_b, if_1)
+                                           .link(n_a, if_1, n_c, if_1)
+                                           .link(n_b, if_2, n_d, if_1)
+                                           .build()
+                                           .unwrap_err();
+        assert_eq!(errors, vec![
+            TopologyError::InterfaceExists(n_a, if_1),
+            TopologyError::NodeNotFound(n_d),
+            TopologyError::DanglingNeighbor(n_b, if_2, n_d),
+            TopologyError::AsymmetricLink(n_c, if_1, n_a, if_1),
+        ]);
+    }
+
+    #[test]
+    fn gateway_and_app_predicates() {
+        let (n_a, n_b, n_c) = (NodeId(0xA), NodeId(0xB), NodeId(0xC));
+        let topo = create_line_topology_with_internet();
+
+        assert!(topo.get_node(n_c).is_gateway());
+        assert!(!topo.get_node(n_b).is_gateway());
+        assert!(topo.get_node(n_b).is_app_endpoint());
+        assert!(!TopologyNode::new(NodeId(0x1)).is_app_endpoint());
+
+        assert_eq!(topo.is_gateway(n_c), Some(true));
+        assert_eq!(topo.is_gateway(n_a), Some(false));
+        assert_eq!(topo.is_gateway(NodeId(0x1)), None);
+        assert_eq!(topo.is_app_endpoint(n_a), Some(true));
+        assert_eq!(topo.is_app_endpoint(NodeId(0x1)), None);
+    }
+
+    #[test]
+    fn app_endpoints() {
+        let topo = create_big_topology();
+        let all: Vec<NodeId> = (0xA..=0xF).map(NodeId).collect();
+        assert_eq!(topo.app_endpoints(), all);
+
+        let mut topo = create_line_topology();
+        topo.add_node(TopologyNode::new(NodeId(0x1)));
+        assert_eq!(topo.app_endpoints(), vec![NodeId(0xA), NodeId(0xB), NodeId(0xC)]);
+    }
+
+    #[test]
+    fn connectivity_tracker() {
+        let (n_a, n_b, n_c, n_d) = (NodeId(0xA), NodeId(0xB), NodeId(0xC), NodeId(0xD));
+        let mut topo = create_line_topology();
+        topo.add_node(TopologyNode::new(n_d));
+
+        let mut tracker = topo.connectivity_tracker();
+        assert!(tracker.connected(n_a, n_c));
+        assert!(tracker.connected(n_d, n_d));
+        assert!(!tracker.connected(n_a, n_d));
+        assert!(!tracker.connected(n_a, NodeId(0x1)));
+
+        topo.connect_auto(n_c, n_d, InterfaceType::LocalNet).unwrap();
+        assert!(tracker.union(n_c, n_d));
+        assert!(!tracker.union(n_a, n_d));
+        assert!(tracker.connected(n_a, n_d));
+        assert_eq!(tracker.connected(n_b, n_d), topo.is_connected());
+    }
+
+    #[test]
+    fn paths_by_egress() {
+        let (n_a, n_c, n_d) = (NodeId(0xA), NodeId(0xC), NodeId(0xD));
+        let mut topo = create_big_topology();
+        let mut island = TopologyNode::new(NodeId(0x1));
+        island.add_iface(Interface::new(IfaceIndex(0), InterfaceType::Internet, vec![]));
+        topo.add_node(island);
+
+        let egress = topo.paths_by_egress(n_d);
+        assert_eq!(egress.len(), 2);
+
+        let via_a = &egress[&(n_a, IfaceIndex(1))];
+        let via_c = &egress[&(n_c, IfaceIndex(2))];
+        assert_eq!(via_a.len(), topo.count_paths(n_d, n_a));
+        assert_eq!(via_c.len(), topo.count_paths(n_d, n_c));
+        assert_eq!(path_ids(&via_c[0]), vec![n_d, NodeId(0xE), n_c]);
+        assert_eq!(via_c[0].to_string(), "Path: (0)D(1) => (1)E(4) => (3)C(2)");
+
+        // no app interface to start from
+        topo.add_node(TopologyNode::new(NodeId(0x2)));
+        topo.connect(NodeId(0x2), IfaceIndex(0), n_d, IfaceIndex(7)).unwrap();
+        assert!(topo.paths_by_egress(NodeId(0x2)).is_empty());
+        assert!(topo.paths_by_egress(NodeId(0x3)).is_empty());
+    }
+
+    #[test]
+    fn find_path_start_is_finish() {
+        let n_a = NodeId(0xA);
+        let (if_0, if_1) = (IfaceIndex(0), IfaceIndex(1));
+        let topo = create_line_topology();
+
+        let paths = topo.find_paths(n_a, if_0, n_a, if_1).unwrap();
+        assert_eq!(paths.len(), 1);
+        assert_eq!(paths[0].to_string(), "Path: (0)A(1)");
+
+        let mut path_vec: Vec<Path> = Vec::new();
+        assert!(!topo.find_path(n_a, if_1, n_a, if_1, &mut Path::new(), &mut path_vec));
+        assert!(path_vec.is_empty());
+        assert_eq!(topo.find_paths_local_only(n_a, if_1, n_a, if_1), Err(PathError::NoRoute));
+    }
+
+    #[test]
+    fn find_paths_order() {
+        let (n_c, n_d) = (NodeId(0xC), NodeId(0xD));
+
+        let paths: Vec<String> = create_big_topology().find_paths(n_d, IfaceIndex(0), n_c, IfaceIndex(2))
+                                                      .unwrap()
+                                                      .iter()
+                                                      .map(Path::to_string)
+                                                      .collect();
+        assert_eq!(paths, vec![
+            "Path: (0)D(1) => (1)E(2) => (2)B(4) => (1)C(2)",
+            "Path: (0)D(1) => (1)E(3) => (3)B(4) => (1)C(2)",
+            "Path: (0)D(1) => (1)E(4) => (3)C(2)",
+            "Path: (0)D(1) => (1)E(5) => (1)F(2) => (4)C(2)",
+        ]);
+
+        // every topology gets its own hash seed
+        for _ in 0..8 {
+            let again: Vec<String> = create_big_topology().find_paths(n_d, IfaceIndex(0), n_c, IfaceIndex(2))
+                                                          .unwrap()
+                                                          .iter()
+                                                          .map(Path::to_string)
+                                                          .collect();
+            assert_eq!(again, paths);
+        }
+    }
+
+    #[test]
+    fn find_paths_complete_on_big_topology() {
+        let mut topo = create_big_topology();
+        let if_0 = IfaceIndex(0);
+
+        // worked out by hand from the diagram above `create_big_topology`,
+        // with a pricier E-C link so the costs tell paths apart
+        topo.get_node_mut(NodeId(0xE)).ifaces.get_mut(&IfaceIndex(4)).unwrap().set_cost(5);
+        let expected = [
+            (NodeId(0xD), NodeId(0xC), vec![
+                ("Path: (0)D(1) => (1)E(2) => (2)B(4) => (1)C(0)", 3),
+                ("Path: (0)D(1) => (1)E(3) => (3)B(4) => (1)C(0)", 3),
+                ("Path: (0)D(1) => (1)E(4) => (3)C(0)", 6),
+                ("Path: (0)D(1) => (1)E(5) => (1)F(2) => (4)C(0)", 3),
+            ]),
+            (NodeId(0xA), NodeId(0xF), vec![
+                ("Path: (0)A(2) => (1)B(2) => (2)E(4) => (3)C(4) => (2)F(0)", 8),
+                ("Path: (0)A(2) => (1)B(2) => (2)E(5) => (1)F(0)", 3),
+                ("Path: (0)A(2) => (1)B(3) => (3)E(4) => (3)C(4) => (2)F(0)", 8),
+                ("Path: (0)A(2) => (1)B(3) => (3)E(5) => (1)F(0)", 3),
+                ("Path: (0)A(2) => (1)B(4) => (1)C(3) => (4)E(5) => (1)F(0)", 4),
+                ("Path: (0)A(2) => (1)B(4) => (1)C(4) => (2)F(0)", 3),
+            ]),
+        ];
+        for (start_id, finish_id, paths) in expected {
+            let found: Vec<(String, u32)> = topo.find_paths_detailed(start_id, if_0, finish_id, if_0)
+                                                .unwrap()
+                                                .into_iter()
+                                                .map(|x| (x.path.to_string(), x.cost))
+                                                .collect();
+            let paths: Vec<(String, u32)> = paths.into_iter().map(|(path, cost)| (path.to_string(), cost)).collect();
+            assert_eq!(found, paths, "{start_id} -> {finish_id}");
+        }
+
+        let ids = topo.nodes_where(|_| true);
+        for &start_id in &ids {
+            for &finish_id in ids.iter().filter(|&&x| x != start_id) {
+                let mut found: Vec<String> = topo.find_paths(start_id, if_0, finish_id, if_0)
+                                                 .unwrap()
+                                                 .iter()
+                                                 .map(Path::to_string)
+                                                 .collect();
+                let mut all: Vec<String> = topo.paths_best_first(start_id, if_0, finish_id, if_0)
+                                               .map(|x| x.to_string())
+                                               .collect();
+                found.sort();
+                all.sort();
+                assert_eq!(found, all, "{start_id} -> {finish_id}");
+                assert_eq!(found.len(), topo.count_paths(start_id, finish_id));
+            }
+        }
+    }
+
+    #[test]
+    fn find_paths_shared_segment() {
+        let (n_1, n_2, n_3, n_4) = (NodeId(0x1), NodeId(0x2), NodeId(0x3), NodeId(0x4));
+        let (if_0, if_1, if_2) = (IfaceIndex(0), IfaceIndex(1), IfaceIndex(2));
+
+        // 1 sits on a segment with 2 and 3, both of which link to 4
+        let mut topo = Topology::new();
+        let mut node_1 = TopologyNode::new(n_1);
+        node_1.add_iface(Interface::new(if_0, InterfaceType::LocalApp, vec![]));
+        node_1.add_iface(Interface::new(if_1, InterfaceType::LocalNet, vec![(n_2, if_1), (n_3, if_1)]));
+        topo.add_node(node_1);
+        for id in [n_2, n_3] {
+            let mut node = TopologyNode::new(id);
+            node.add_iface(Interface::new(if_1, InterfaceType::LocalNet, vec![(n_1, if_1)]));
+            topo.add_node(node);
+        }
+        let mut node_4 = TopologyNode::new(n_4);
+        node_4.add_iface(Interface::new(if_0, InterfaceType::LocalApp, vec![]));
+        topo.add_node(node_4);
+        topo.connect(n_2, if_2, n_4, if_1).unwrap();
+        topo.connect(n_3, if_2, n_4, if_2).unwrap();
+
+        let paths: Vec<String> = topo.find_paths(n_1, if_0, n_4, if_0)
+                                     .unwrap()
+                                     .iter()
+                                     .map(Path::to_string)
+                                     .collect();
+        assert_eq!(paths, vec![
+            "Path: (0)1(1) => (1)2(2) => (1)4(0)",
+            "Path: (0)1(1) => (1)3(2) => (2)4(0)",
+        ]);
+    }
+
+    #[test]
+    fn add_neighbors() {
+        let (n_a, n_b, n_c, n_x) = (NodeId(0xA), NodeId(0xB), NodeId(0xC), NodeId(0x1));
+        let (if_1, if_2, if_3) = (IfaceIndex(1), IfaceIndex(2), IfaceIndex(3));
+        let mut topo = create_line_topology();
+
+        // a second neighbor on B's existing interface towards C
+        topo.add_node(TopologyNode::new(n_x));
+        topo.add_neighbor(n_b, if_2, n_x, if_1).unwrap();
+        topo.add_neighbor(n_b, if_2, n_x, if_1).unwrap();
+        topo.add_neighbor(n_x, if_1, n_b, if_2).unwrap();
+        assert_eq!(topo.get_node(n_b).ifaces[&if_2].neighbors(), &[(n_c, if_1), (n_x, if_1)]);
+        assert_eq!(topo.get_node(n_x).ifaces[&if_1].iface_type(), InterfaceType::LocalNet);
+        assert!(topo.asymmetric_links().is_empty());
+
+        // one side only so far
+        topo.add_neighbor(n_a, if_3, n_c, if_3).unwrap();
+        assert_eq!(topo.asymmetric_links(), vec![(n_a, if_3, n_c, if_3)]);
+
+        assert_eq!(topo.add_neighbor(NodeId(0x2), if_1, n_a, if_1), Err(TopologyError::NodeNotFound(NodeId(0x2))));
+        assert_eq!(topo.add_neighbor(n_a, if_2, n_a, if_1), Err(TopologyError::SelfLoop(n_a, if_2)));
+
+        let mut iface = Interface::new(if_1, InterfaceType::LocalNet, vec![]);
+        iface.add_neighbor(n_a, if_1);
+        iface.add_neighbor(n_a, if_1);
+        assert_eq!(iface.neighbors(), &[(n_a, if_1)]);
+    }
+
+    #[test]
+    fn find_paths_allow_revisit() {
+        let (n_a, n_c, n_d) = (NodeId(0xA), NodeId(0xC), NodeId(0xD));
+        let (if_0, if_2) = (IfaceIndex(0), IfaceIndex(2));
+
+        let topo = create_big_topology();
+        assert_eq!(topo.find_paths_allow_revisit(n_d, if_0, n_c, if_2, 1), topo.find_paths(n_d, if_0, n_c, if_2));
+        assert_eq!(topo.find_paths_allow_revisit(n_d, if_0, n_c, if_2, 0), Err(PathError::NoRoute));
+
+        // E may come back from B over the other B-E link, but never over
+        // the one it left by
+        let paths: Vec<String> = topo.find_paths_allow_revisit(n_d, if_0, n_c, if_2, 2)
+                                     .unwrap()
+                                     .iter()
+                                     .map(Path::to_string)
+                                     .collect();
+        assert!(paths.contains(&"Path: (0)D(1) => (1)E(2) => (2)B(3) => (3)E(4) => (3)C(2)".to_string()));
+        assert!(!paths.iter().any(|x| x.contains("(2)B(2) => (2)E") || x.contains("(3)B(3) => (3)E")));
+
+        // going back and forth over A-B isn't a second route
+        let topo = create_line_topology();
+        let paths: Vec<String> = topo.find_paths_allow_revisit(n_a, if_0, n_c, if_0, 2)
+                                     .unwrap()
+                                     .iter()
+                                     .map(Path::to_string)
+                                     .collect();
+        assert_eq!(paths, vec!["Path: (0)A(1) => (1)B(2) => (1)C(0)"]);
+    }
+
+    #[test]
+    fn nodes_within() {
+        let (n_a, n_b, n_c, n_d, n_e, n_f) = (NodeId(0xA), NodeId(0xB), NodeId(0xC), NodeId(0xD), NodeId(0xE), NodeId(0xF));
+        let topo = create_big_topology();
+
+        assert_eq!(topo.nodes_within(n_a, 0), vec![(n_a, 0)]);
+        assert_eq!(topo.nodes_within(n_a, 2), vec![(n_a, 0), (n_b, 1), (n_c, 2), (n_e, 2)]);
+        assert_eq!(topo.nodes_within(n_d, 10), vec![(n_d, 0), (n_e, 1), (n_b, 2), (n_c, 2), (n_f, 2), (n_a, 3)]);
+        assert!(topo.nodes_within(NodeId(0x1), 3).is_empty());
+    }
+
+    #[test]
+    fn interface_type_counts() {
+        let counts = create_big_topology().interface_type_counts();
+        assert_eq!(counts, HashMap::from([
+            (InterfaceType::LocalApp, 6),
+            (InterfaceType::LocalNet, 16),
+            (InterfaceType::Internet, 2),
+        ]));
+
+        let counts = create_line_topology().interface_type_counts();
+        assert_eq!(counts.get(&InterfaceType::Internet), None);
+        assert!(Topology::new().interface_type_counts().is_empty());
+    }
+
+    #[test]
+    fn best_link_to_add() {
+        let (n_a, n_b, n_c) = (NodeId(0xA), NodeId(0xB), NodeId(0xC));
+
+        assert_eq!(create_line_topology().best_link_to_add(), Some((n_a, n_c, 1)));
+        assert_eq!(create_ring_topology().best_link_to_add(), Some((n_a, n_c, 2)));
+
+        let mut topo = create_big_topology();
+        let (a, b, diameter) = topo.best_link_to_add().unwrap();
+        assert!(diameter < topo.diameter().unwrap());
+        topo.connect_auto(a, b, InterfaceType::LocalNet).unwrap();
+        assert_eq!(topo.diameter(), Some(diameter));
+
+        let mut topo = Topology::new();
+        topo.add_node(TopologyNode::new(n_a));
+        assert_eq!(topo.best_link_to_add(), None);
+        topo.add_node(TopologyNode::new(n_b));
+        assert_eq!(topo.best_link_to_add(), Some((n_a, n_b, 1)));
+    }
+
+    #[test]
+    fn split_node() {
+        let (n_a, n_b, n_c, n_e, n_f) = (NodeId(0xA), NodeId(0xB), NodeId(0xC), NodeId(0xE), NodeId(0xF));
+        let n_x = NodeId(0x1E);
+        let (if_3, if_4, if_5) = (IfaceIndex(3), IfaceIndex(4), IfaceIndex(5));
+
+        let mut topo = create_big_topology();
+        topo.split_node(n_e, n_x, &[if_4, if_5]).unwrap();
+        assert_eq!(topo.used_ifaces(n_e), vec![IfaceIndex(0), IfaceIndex(1), IfaceIndex(2), if_3]);
+        assert_eq!(topo.used_ifaces(n_x), vec![if_4, if_5]);
+        assert_eq!(topo.get_node(n_c).ifaces[&if_3].neighbors(), &[(n_x, if_4)]);
+        assert_eq!(topo.get_node(n_f).ifaces[&IfaceIndex(1)].neighbors(), &[(n_x, if_5)]);
+        assert!(topo.asymmetric_links().is_empty());
+        assert!(topo.dangling_neighbors().is_empty());
+
+        assert_eq!(topo.split_node(n_e, n_a, &[]), Err(TopologyError::NodeExists(n_a)));
+        assert_eq!(topo.split_node(n_e, NodeId(0x2), &[if_4]), Err(TopologyError::InterfaceNotFound(n_e, if_4)));
+        assert_eq!(topo.split_node(NodeId(0x2), NodeId(0x3), &[]), Err(TopologyError::NodeNotFound(NodeId(0x2))));
+        assert!(!topo.nodes.contains_key(&NodeId(0x2)));
+
+        let mut topo = create_line_topology();
+        let n_y = NodeId(0x1B);
+        assert_eq!(topo.split_node_linked(n_b, n_y, &[IfaceIndex(2)]), Ok((IfaceIndex(2), IfaceIndex(0))));
+        assert!(topo.is_connected());
+        assert_eq!(path_ids(&topo.shortest_path(n_a, n_c).unwrap()), vec![n_a, n_b, n_y, n_c]);
+    }
+
+    #[test]
+    fn contract_edge() {
+        let (n_a, n_b, n_c, n_e) = (NodeId(0xA), NodeId(0xB), NodeId(0xC), NodeId(0xE));
+
+        // both parallel B-E links go, B's app, A and C links land on E
+        let mut topo = create_big_topology();
+        topo.contract_edge(n_e, n_b).unwrap();
+        assert!(!topo.nodes.contains_key(&n_b));
+        assert_eq!(topo.used_ifaces(n_e), (0..=6).map(IfaceIndex).collect::<Vec<_>>());
+        assert_eq!(topo.get_node(n_e).ifaces[&IfaceIndex(2)].iface_type(), InterfaceType::LocalApp);
+        assert_eq!(topo.get_node(n_a).ifaces[&IfaceIndex(2)].neighbors(), &[(n_e, IfaceIndex(3))]);
+        assert_eq!(topo.get_node(n_c).ifaces[&IfaceIndex(1)].neighbors(), &[(n_e, IfaceIndex(6))]);
+        assert!(topo.asymmetric_links().is_empty());
+        assert!(topo.dangling_neighbors().is_empty());
+        assert_eq!(topo.count_paths(n_a, n_c), 3);
+
+        assert_eq!(topo.contract_edge(n_a, n_c), Err(TopologyError::NotAdjacent(n_a, n_c)));
+        assert_eq!(topo.contract_edge(n_a, n_a), Err(TopologyError::NotAdjacent(n_a, n_a)));
+        assert_eq!(topo.contract_edge(n_a, n_b), Err(TopologyError::NodeNotFound(n_b)));
+
+        let mut topo = create_line_topology();
+        topo.contract_edge(n_a, n_b).unwrap();
+        assert_eq!(topo.used_ifaces(n_a), vec![IfaceIndex(0), IfaceIndex(1), IfaceIndex(2)]);
+        assert_eq!(path_ids(&topo.shortest_path(n_a, n_c).unwrap()), vec![n_a, n_c]);
+    }
+
+    #[test]
+    fn path_errors() {
+        let (n_a, n_c, n_x) = (NodeId(0xA), NodeId(0xC), NodeId(0x1));
+        let if_0 = IfaceIndex(0);
+        let mut topo = create_line_topology();
+        let mut isolated = TopologyNode::new(n_x);
+        isolated.add_iface(Interface::new(if_0, InterfaceType::LocalApp, vec![]));
+        topo.add_node(isolated);
+
+        assert_eq!(topo.find_paths(NodeId(0x2), if_0, n_c, if_0), Err(PathError::StartNotFound(NodeId(0x2))));
+        assert_eq!(topo.find_paths(n_a, if_0, NodeId(0x2), if_0), Err(PathError::FinishNotFound(NodeId(0x2))));
+        assert_eq!(topo.find_paths(n_a, IfaceIndex(7), n_c, if_0), Err(PathError::StartInterfaceInvalid(n_a, IfaceIndex(7))));
+        assert_eq!(topo.find_paths(n_a, if_0, n_x, if_0), Err(PathError::NoRoute));
+        assert_eq!(topo.find_paths(n_a, if_0, n_c, IfaceIndex(7)), Err(PathError::FinishInterfaceInvalid(n_c, IfaceIndex(7))));
+        assert_eq!(topo.find_paths_local_only(n_x, IfaceIndex(7), n_a, if_0), Err(PathError::StartInterfaceInvalid(n_x, IfaceIndex(7))));
+        assert_eq!(topo.walk_paths(n_a, if_0, n_x, IfaceIndex(7), &mut PathCollector(&mut Vec::new())),
+                   Err(PathError::FinishInterfaceInvalid(n_x, IfaceIndex(7))));
+        assert_eq!(topo.find_paths_limited(n_a, if_0, n_x, if_0, 3), Err(PathError::NoRoute));
+        assert_eq!(topo.find_paths_limited(n_a, if_0, n_c, if_0, 0), Ok(vec![]));
+        assert_eq!(topo.unique_path(n_a, if_0, NodeId(0x2), if_0), Err(PathError::FinishNotFound(NodeId(0x2))));
+    }
+
+    #[test]
+    fn flow_choke_points() {
+        let (n_a, n_b, n_c, n_d, n_e) = (NodeId(0xA), NodeId(0xB), NodeId(0xC), NodeId(0xD), NodeId(0xE));
+        let topo = create_big_topology();
+
+        // D -> E -> C, D -> E -> B -> A, C -> B
+        let demands = [(n_d, n_c, 40), (n_d, n_a, 30), (n_c, n_b, 5), (n_d, NodeId(0x1), 100)];
+        assert_eq!(topo.flow_choke_points(&demands), vec![(n_e, 70), (n_b, 30)]);
+        assert!(topo.flow_choke_points(&[(n_c, n_b, 5), (n_e, n_e, 10)]).is_empty());
+    }
+
+    #[test]
+    fn ascii_table() {
+        let mut topo = create_big_topology();
+        topo.add_node(TopologyNode::new(NodeId(0x1)));
+
+        let expected = [
+            "node  role     degree  neighbors",
+            "1     core          0",
+            "A     gateway       1  B",
+            "B     app           4  A, C, E",
+            "C     gateway       3  B, E, F",
+            "D     app           1  E",
+            "E     app           5  B, C, D, F",
+            "F     app           2  C, E",
+            "",
+        ];
+        assert_eq!(topo.to_ascii_table(), expected.join("\n"));
+    }
+
+    #[test]
+    fn paths_within_budget() {
+        let (n_b, n_c, n_d, n_e, n_f) = (NodeId(0xB), NodeId(0xC), NodeId(0xD), NodeId(0xE), NodeId(0xF));
+        let mut topo = create_big_topology();
+
+        // the 2-hop D -> E -> C costs 101, the 3-hop detours 3
+        topo.get_node_mut(n_e).ifaces.get_mut(&IfaceIndex(4)).unwrap().set_cost(100);
+
+        let paths = topo.paths_within_budget(n_d, n_c, 4);
+        assert_eq!(paths.iter().map(path_ids).collect::<Vec<_>>(), vec![
+            vec![n_d, n_e, n_b, n_c],
+            vec![n_d, n_e, n_b, n_c],
+            vec![n_d, n_e, n_f, n_c],
+        ]);
+        assert!(paths.iter().all(|x| x.total_cost(&topo) <= 4));
+
+        assert_eq!(topo.paths_within_budget(n_d, n_c, 101).len(), 4);
+        assert!(topo.paths_within_budget(n_d, n_c, 2).is_empty());
+        assert_eq!(topo.paths_within_budget(n_d, n_d, 0).len(), 1);
+
+        // same search as find_paths, so links that are down are skipped too
+        topo.set_admin_up(n_b, IfaceIndex(4), false).unwrap();
+        assert_eq!(topo.paths_within_budget(n_d, n_c, 4).iter().map(path_ids).collect::<Vec<_>>(), vec![vec![n_d, n_e, n_f, n_c]]);
+    }
+
+    #[test]
+    fn node_and_edge_count() {
+        let topo = create_big_topology();
+        assert_eq!(topo.node_count(), 6);
+        assert_eq!(topo.edge_count(), 8);
+        assert!(!topo.is_empty());
+
+        let topo = create_line_topology_with_internet();
+        assert_eq!((topo.node_count(), topo.edge_count()), (3, 2));
+
+        let topo = Topology::new();
+        assert_eq!((topo.node_count(), topo.edge_count()), (0, 0));
+        assert!(topo.is_empty());
+    }
+
+    #[test]
+    fn interfaces_between() {
+        let (n_a, n_b, n_c, n_e) = (NodeId(0xA), NodeId(0xB), NodeId(0xC), NodeId(0xE));
+        let topo = create_big_topology();
+
+        assert_eq!(topo.interfaces_between(n_b, n_e), vec![(IfaceIndex(2), IfaceIndex(2)), (IfaceIndex(3), IfaceIndex(3))]);
+        assert_eq!(topo.interfaces_between(n_c, n_b), vec![(IfaceIndex(1), IfaceIndex(4))]);
+        assert!(topo.interfaces_between(n_a, n_c).is_empty());
+        assert!(topo.interfaces_between(NodeId(0x1), n_a).is_empty());
+    }
+
+    #[test]
+    fn allocate_node_id() {
+        let mut topo = Topology::new();
+        assert_eq!(topo.allocate_node_id(), NodeId(0));
+
+        for _ in 0..3 {
+            topo.add_node(TopologyNode::new(topo.allocate_node_id()));
+        }
+        assert_eq!(topo.allocate_node_id(), NodeId(3));
+
+        topo.add_node(TopologyNode::new(NodeId(5)));
+        assert_eq!(topo.allocate_node_id(), NodeId(3));
+        assert_eq!(create_big_topology().allocate_node_id(), NodeId(0));
+    }
+
+    #[test]
+    fn shortest_path_min_bandwidth() {
+        let (n_b, n_c, n_d, n_e) = (NodeId(0xB), NodeId(0xC), NodeId(0xD), NodeId(0xE));
+        let mut topo = create_big_topology();
+        topo.get_node_mut(n_c).ifaces.get_mut(&IfaceIndex(3)).unwrap().set_bandwidth(10);
+
+        // C's side of the direct E - C link is too narrow
+        assert_eq!(topo.shortest_path_min_bandwidth(n_d, n_c, 10), topo.shortest_path(n_d, n_c));
+        let path = topo.shortest_path_min_bandwidth(n_d, n_c, 50).unwrap();
+        assert_eq!(path_ids(&path), vec![n_d, n_e, n_b, n_c]);
+
+        topo.get_node_mut(n_d).ifaces.get_mut(&IfaceIndex(1)).unwrap().set_bandwidth(10);
+        assert_eq!(topo.shortest_path_min_bandwidth(n_d, n_c, 50), None);
+    }
+
+    #[test]
+    fn routing_tables() {
+        let (n_a, n_b, n_c, n_d, n_e) = (NodeId(0xA), NodeId(0xB), NodeId(0xC), NodeId(0xD), NodeId(0xE));
+        let topo = create_big_topology();
+
+        let table = topo.routing_table(n_d);
+        assert_eq!(table.len(), 5);
+        assert!(table.iter().all(|route| route.iface == IfaceIndex(1) && route.next_hop == n_e));
+        assert_eq!(topo.routing_table(n_b)[0], Route { destination: n_a, iface: IfaceIndex(1), next_hop: n_a });
+        assert_eq!(topo.routing_table(n_a).iter().find(|route| route.destination == n_c).unwrap().next_hop, n_b);
+        assert!(topo.routing_table(NodeId(0x1)).is_empty());
+
+        let expected = [
+            "A: dest=B via iface=1 nexthop=B",
+            "A: dest=C via iface=1 nexthop=B",
+            "B: dest=A via iface=1 nexthop=A",
+            "B: dest=C via iface=2 nexthop=C",
+            "C: dest=A via iface=1 nexthop=B",
+            "C: dest=B via iface=1 nexthop=B",
+            "",
+        ];
+        assert_eq!(create_line_topology().dump_fibs(), expected.join("\n"));
+    }
+
+    #[test]
+    fn link_admin_state() {
+        let (n_a, n_b, n_c) = (NodeId(0xA), NodeId(0xB), NodeId(0xC));
+        let (if_0, if_1, if_2) = (IfaceIndex(0), IfaceIndex(1), IfaceIndex(2));
+        let mut topo = create_line_topology();
+        assert_eq!(topo.find_paths(n_a, if_0, n_c, if_0).unwrap().len(), 1);
+
+        topo.set_admin_up(n_b, if_2, false).unwrap();
+        assert_eq!(topo.down_links(), vec![(n_b, if_2)]);
+        assert_eq!(topo.find_paths(n_a, if_0, n_c, if_0), Err(PathError::NoRoute));
+        assert!(topo.shortest_path(n_a, n_c).is_none());
+        assert_eq!(topo.edge_count(), 2);
+
+        topo.set_oper_up(n_a, if_1, false).unwrap();
+        let stats = topo.stats();
+        assert_eq!((stats.nodes, stats.links, stats.admin_down_links, stats.oper_down_links), (3, 2, 1, 1));
+        assert_eq!(stats.to_string(), "3 nodes, 2 links, 1 link administratively down, 1 link operationally down");
+        assert_eq!(Topology::new().stats().to_string(), "0 nodes, 0 links, 0 links administratively down, 0 links operationally down");
+
+        topo.set_admin_up(n_b, if_2, true).unwrap();
+        topo.set_oper_up(n_a, if_1, true).unwrap();
+        assert!(topo.down_links().is_empty());
+        assert_eq!(topo.find_paths(n_a, if_0, n_c, if_0).unwrap().len(), 1);
+
+        assert_eq!(topo.set_admin_up(NodeId(0x1), if_0, false), Err(TopologyError::NodeNotFound(NodeId(0x1))));
+        assert_eq!(topo.set_oper_up(n_a, if_2, false), Err(TopologyError::InterfaceNotFound(n_a, if_2)));
+    }
+
+    #[test]
+    fn gateway_resilience() {
+        let mut topo = create_big_topology();
+        topo.add_node(TopologyNode::new(NodeId(0x1)));
+
+        assert_eq!(topo.gateway_resilience(NodeId(0xD)), 2);
+        assert_eq!(topo.gateway_resilience(NodeId(0x1)), 0);
+        assert_eq!(create_line_topology_with_internet().gateway_resilience(NodeId(0xA)), 1);
+    }
+
+    #[test]
+    fn canonicalize() {
+        let (n_a, n_b, n_c) = (NodeId(0xA), NodeId(0xB), NodeId(0xC));
+        let mut topo = create_line_topology();
+        topo.get_node_mut(n_b).ifaces.get_mut(&IfaceIndex(1)).unwrap().neighbors.push((n_a, IfaceIndex(1)));
+        topo.get_node_mut(n_b).ifaces.get_mut(&IfaceIndex(1)).unwrap().neighbors.insert(0, (n_c, IfaceIndex(5)));
+        topo.canonicalize();
+        assert_eq!(topo.get_node(n_b).ifaces[&IfaceIndex(1)].neighbors(), &vec![(n_a, IfaceIndex(1)), (n_c, IfaceIndex(5))]);
+
+        // Same line with other interface indices
+        let mut renumbered = TopologyBuilder::new()
+            .node(n_a).node(n_b).node(n_c)
+            .app(n_a, IfaceIndex(7)).app(n_b, IfaceIndex(3)).app(n_c, IfaceIndex(9))
+            .link(n_a, IfaceIndex(4), n_b, IfaceIndex(6))
+            .link(n_b, IfaceIndex(0), n_c, IfaceIndex(2))
+            .build()
+            .unwrap();
+        assert_ne!(renumbered, create_line_topology());
+        renumbered.renumber_interfaces();
+        let mut line = create_line_topology();
+        line.renumber_interfaces();
+        assert_eq!(renumbered, line);
+    }
+
+    #[test]
+    fn walk_paths() {
+        struct FirstPath {
+            entered: usize,
+            pruned: usize,
+            path: Option<Path>,
+        }
+
+        impl PathVisitor for FirstPath {
+            fn on_enter(&mut self, _node: NodeId) {
+                self.entered += 1;
+            }
+
+            fn on_complete(&mut self, path: &Path, _hops: usize, _cost: u32) -> ControlFlow<()> {
+                self.path = Some(path.clone());
+                ControlFlow::Break(())
+            }
+
+            fn on_prune(&mut self, _node: NodeId) {
+                self.pruned += 1;
+            }
+        }
+
+        let (n_c, n_d) = (NodeId(0xC), NodeId(0xD));
+        let if_0 = IfaceIndex(0);
+        let topo = create_big_topology();
+        let mut visitor = FirstPath { entered: 0, pruned: 0, path: None };
+        topo.walk_paths(n_d, if_0, n_c, if_0, &mut visitor).unwrap();
+
+        assert_eq!(visitor.path.as_ref(), topo.find_paths(n_d, if_0, n_c, if_0).unwrap().first());
+        assert!(visitor.entered >= visitor.path.as_ref().unwrap().nodes.len());
+        assert!(visitor.pruned > 0);
+        assert_eq!(topo.walk_paths(NodeId(0x1), if_0, n_c, if_0, &mut visitor), Err(PathError::StartNotFound(NodeId(0x1))));
+    }
+
+    #[test]
+    fn edge_betweenness() {
+        let (n_a, n_b, n_c) = (NodeId(0xA), NodeId(0xB), NodeId(0xC));
+        let (if_1, if_2, if_3) = (IfaceIndex(1), IfaceIndex(2), IfaceIndex(3));
+        let topo = TopologyBuilder::new()
+            .node(n_a).node(n_b).node(n_c).node(NodeId(0x1))
+            .link(n_a, if_1, n_b, if_1)
+            .link(n_a, if_2, n_b, if_2)
+            .link(n_c, if_1, n_b, if_3)
+            .build()
+            .unwrap();
+
+        let scores = topo.edge_betweenness();
+        assert_eq!(scores.len(), 3);
+        assert_eq!(scores[&(n_a, if_1, n_b, if_1)], 1.0);
+        assert_eq!(scores[&(n_a, if_2, n_b, if_2)], 1.0);
+        assert_eq!(scores[&(n_b, if_3, n_c, if_1)], 2.0);
+
+        let ring = create_ring_topology().edge_betweenness();
+        assert_eq!(ring.len(), create_ring_topology().edge_count());
+        let total: f64 = ring.values().sum();
+        let hops: usize = create_ring_topology().all_pairs_shortest_hops().values().flat_map(|x| x.values()).sum();
+        assert!((total - hops as f64 / 2.0).abs() < 1e-9);
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn yaml_round_trip() {
+        let topo = create_big_topology();
+        let yaml = topo.to_yaml();
+        assert_eq!(Topology::from_yaml(&yaml).unwrap().to_yaml(), yaml);
+
+        let line = Topology::from_yaml("
+nodes: [10, 11, 12]
+links:
+  - {a: 10, a_if: 1, b: 11, b_if: 1}
+  - {a: 11, a_if: 2, b: 12, b_if: 1}
+gateways:
+  - {node: 12, iface: 2}
+apps:
+  - {node: 10, iface: 0}
+  - {node: 11, iface: 0}
+  - {node: 12, iface: 0}
+").unwrap();
+        assert_eq!(line, create_line_topology_with_internet());
+
+        match Topology::from_yaml("nodes: [10]\nlinks:\n  - {a: 10, a_if: 1, b: 11, b_if: 1}\n") {
+            Err(YamlError::Invalid(errors)) => assert_eq!(errors, vec![TopologyError::NodeNotFound(NodeId(11))]),
+            other => panic!("unexpected result {:?}", other),
+        }
+        assert!(matches!(Topology::from_yaml("links: []"), Err(YamlError::Parse(_))));
+
+        let mut shared = create_line_topology();
+        shared.add_segment(&[(NodeId(0xA), IfaceIndex(3)), (NodeId(0xB), IfaceIndex(3)), (NodeId(0xC), IfaceIndex(3))]).unwrap();
+        let yaml = shared.to_yaml();
+        assert!(yaml.contains("segments:\n- - node: 10\n    iface: 3\n  - node: 11\n    iface: 3\n  - node: 12\n    iface: 3\n"));
+        assert_eq!(Topology::from_yaml(&yaml).unwrap(), shared);
+    }
+
+    #[test]
+    fn girth() {
+        let (n_b, n_e) = (NodeId(0xB), NodeId(0xE));
+        assert_eq!(create_line_topology().girth(), None);
+        assert_eq!(Topology::new().shortest_cycle(), None);
+
+        // parallel B-E links
+        let topo = create_big_topology();
+        assert_eq!(topo.shortest_cycle(), Some(vec![n_b, n_e]));
+
+        let ring = create_ring_topology();
+        assert_eq!(ring.girth(), Some(ring.node_count()));
+        let cycle = ring.shortest_cycle().unwrap();
+        assert_eq!(cycle[0], *ring.nodes.keys().min().unwrap());
+        for (i, &id) in cycle.iter().enumerate() {
+            assert!(ring.are_adjacent(id, cycle[(i + 1) % cycle.len()]));
+        }
+    }
+
+    #[test]
+    fn find_paths_detailed() {
+        let (n_c, n_d) = (NodeId(0xC), NodeId(0xD));
+        let if_0 = IfaceIndex(0);
+        let mut topo = create_big_topology();
+        topo.set_type_costs(3, 1);
+        topo.get_node_mut(NodeId(0xE)).ifaces.get_mut(&IfaceIndex(4)).unwrap().set_cost(7);
+
+        let paths = topo.find_paths(n_d, if_0, n_c, if_0).unwrap();
+        let results = topo.find_paths_detailed(n_d, if_0, n_c, if_0).unwrap();
+        assert_eq!(results.len(), paths.len());
+        for (result, path) in results.iter().zip(&paths) {
+            assert_eq!(&result.path, path);
+            assert_eq!(result.hops, path.nodes.len() - 1);
+            assert_eq!(result.cost, path.total_cost(&topo));
+        }
+        assert_eq!(topo.find_paths_detailed(n_d, if_0, NodeId(0x1), if_0), Err(PathError::FinishNotFound(NodeId(0x1))));
+    }
+
+    #[test]
+    fn cost_saturates() {
+        let (n_1, n_2, n_3, n_4, n_5) = (NodeId(0x1), NodeId(0x2), NodeId(0x3), NodeId(0x4), NodeId(0x5));
+        let (if_1, if_2) = (IfaceIndex(1), IfaceIndex(2));
+        let mut topo = TopologyBuilder::new()
+            .node(n_1).node(n_2).node(n_3).node(n_4).node(n_5)
+            .link(n_1, if_1, n_2, if_1)
+            .link(n_2, if_2, n_3, if_1)
+            .link(n_3, if_2, n_4, if_1)
+            .link(n_1, if_2, n_5, if_1)
+            .link(n_5, if_2, n_4, if_2)
+            .build()
+            .unwrap();
+        let mut set_cost = |id: NodeId, if_id: IfaceIndex, cost: u32| topo.get_node_mut(id).ifaces.get_mut(&if_id).unwrap().set_cost(cost);
+        // three links of u32::MAX / 2 would wrap around to less than the other route
+        set_cost(n_1, if_1, u32::MAX / 2);
+        set_cost(n_2, if_2, u32::MAX / 2);
+        set_cost(n_3, if_2, u32::MAX / 2);
+        set_cost(n_1, if_2, u32::MAX - 10);
+
+        let path = topo.shortest_path_weighted(n_1, n_4).unwrap();
+        assert_eq!(path.nodes.iter().map(|x| x.id).collect::<Vec<NodeId>>(), vec![n_1, n_5, n_4]);
+        assert_eq!(path.total_cost(&topo), u32::MAX - 9);
+        assert_eq!(topo.distances_from_weighted(n_1)[&n_3], u32::MAX - 8);
+
+        let mut path = Path::new();
+        for (id, forward) in [(n_1, if_1), (n_2, if_2), (n_3, if_2), (n_4, if_1)] {
+            let mut path_node = PathNode::new(id);
+            path_node.forward_if_id = forward;
+            path.nodes.push_back(path_node);
+        }
+        assert_eq!(path.total_cost(&topo), u32::MAX);
+    }
+
+    #[test]
+    fn path_iter() {
+        let (n_b, n_c, n_d, n_e) = (NodeId(0xB), NodeId(0xC), NodeId(0xD), NodeId(0xE));
+        let (if_0, if_1) = (IfaceIndex(0), IfaceIndex(1));
+        let topo = create_big_topology();
+        let path = topo.find_paths(n_d, if_0, n_c, if_0).unwrap().remove(0);
+
+        let hops: Vec<(NodeId, IfaceIndex, IfaceIndex)> = path.iter().map(|x| (x.id(), x.reverse(), x.forward())).collect();
+        assert_eq!(hops, vec![(n_d, if_0, if_1), (n_e, if_1, IfaceIndex(2)), (n_b, IfaceIndex(2), IfaceIndex(4)), (n_c, if_1, if_0)]);
+
+        let mut ids: Vec<NodeId> = Vec::new();
+        for path_node in &path {
+            ids.push(path_node.id());
+        }
+        assert_eq!(ids, vec![n_d, n_e, n_b, n_c]);
+        assert_eq!(Path::new().iter().count(), 0);
+    }
+
+    #[test]
+    fn app_reachability_matrix() {
+        let (n_a, n_b, n_c) = (NodeId(0xA), NodeId(0xB), NodeId(0xC));
+        let mut topo = create_line_topology();
+        let matrix = topo.app_reachability_matrix();
+        assert_eq!(matrix.len(), 6);
+        assert!(matrix.values().all(|&reachable| reachable));
+
+        topo.set_admin_up(n_b, IfaceIndex(2), false).unwrap();
+        let matrix = topo.app_reachability_matrix();
+        assert!(matrix[&(n_a, n_b)] && matrix[&(n_b, n_a)]);
+        assert!(!matrix[&(n_a, n_c)] && !matrix[&(n_c, n_b)]);
+        assert!(!matrix.contains_key(&(n_a, n_a)));
+    }
+
+    #[test]
+    fn find_paths_within() {
+        let (n_c, n_d, n_e, n_f) = (NodeId(0xC), NodeId(0xD), NodeId(0xE), NodeId(0xF));
+        let (if_0, if_2) = (IfaceIndex(0), IfaceIndex(2));
+        let topo = create_big_topology();
+
+        let paths: Vec<String> = topo.find_paths_within(n_d, if_0, n_c, if_2, &HashSet::from([n_c, n_d, n_e, n_f]))
+                                     .unwrap()
+                                     .iter()
+                                     .map(Path::to_string)
+                                     .collect();
+        assert_eq!(paths, vec![
+            "Path: (0)D(1) => (1)E(4) => (3)C(2)",
+            "Path: (0)D(1) => (1)E(5) => (1)F(2) => (4)C(2)",
+        ]);
+        assert_eq!(topo.find_paths_within(n_d, if_0, n_c, if_2, &HashSet::from([n_c, n_d, n_f])), Err(PathError::NoRoute));
+        assert_eq!(topo.find_paths_within(n_d, if_0, n_c, if_2, &HashSet::from([n_c, n_e, n_f])), Err(PathError::NoRoute));
+    }
+
+    #[test]
+    fn link_iface_report() {
+        let (n_a, n_b, n_c) = (NodeId(0xA), NodeId(0xB), NodeId(0xC));
+        let (if_1, if_2) = (IfaceIndex(1), IfaceIndex(2));
+        let mut topo = create_line_topology();
+        assert_eq!(topo.link_iface_report(), vec![(n_a, if_1, n_b, if_1), (n_b, if_2, n_c, if_1)]);
+
+        topo.set_admin_up(n_b, if_2, false).unwrap();
+        assert_eq!(topo.link_iface_report().len(), 2);
+
+        let report = create_big_topology().link_iface_report();
+        assert_eq!(report.len(), create_big_topology().edge_count());
+        assert!(report.windows(2).all(|x| x[0] < x[1]));
+        assert!(report.iter().all(|&(a_id, a_if_id, b_id, b_if_id)| (a_id, a_if_id) < (b_id, b_if_id)));
+    }
+
+    #[test]
+    fn display_truncated() {
+        let (n_c, n_d) = (NodeId(0xC), NodeId(0xD));
+        let topo = create_big_topology();
+        let path = topo.find_paths(n_d, IfaceIndex(0), n_c, IfaceIndex(2)).unwrap().remove(0);
+
+        assert_eq!(path.display_truncated(4), path.to_string());
+        assert_eq!(path.display_truncated(3), "Path: (0)D(1) => (1)E(2) => ... => (1)C(2)");
+        assert_eq!(path.display_truncated(2), "Path: (0)D(1) => ... => (1)C(2)");
+        assert_eq!(path.display_truncated(1), "Path: (0)D(1) => ...");
+        assert_eq!(path.display_truncated(0), "Path: ...");
+    }
+
+    #[test]
+    fn hop_histogram() {
+        let mut topo = create_big_topology();
+        topo.add_node(TopologyNode::new(NodeId(0x1)));
+
+        assert_eq!(topo.hop_histogram(NodeId(0xD)), BTreeMap::from([(0, 1), (1, 1), (2, 3), (3, 1)]));
+        assert_eq!(topo.hop_histogram(NodeId(0x1)), BTreeMap::from([(0, 1)]));
+        assert!(topo.hop_histogram(NodeId(0x2)).is_empty());
+    }
+
+    #[test]
+    fn egress_interfaces() {
+        let (n_a, n_c) = (NodeId(0xA), NodeId(0xC));
+        let mut topo = create_line_topology_with_internet_2();
+        assert_eq!(topo.egress_interfaces(n_a), vec![IfaceIndex(2)]);
+
+        topo.get_node_mut(n_a).add_iface(Interface::new(IfaceIndex(3), InterfaceType::Internet, vec![]));
+        assert_eq!(topo.egress_interfaces(n_a), vec![IfaceIndex(2), IfaceIndex(3)]);
+        assert_eq!(topo.egress_interfaces(n_c), vec![IfaceIndex(2)]);
+        assert!(topo.egress_interfaces(NodeId(0xB)).is_empty());
+        assert!(topo.egress_interfaces(NodeId(0x1)).is_empty());
+    }
+
+    #[test]
+    fn links_to_connect() {
+        let (n_1, n_2, n_a) = (NodeId(0x1), NodeId(0x2), NodeId(0xA));
+        assert!(create_big_topology().links_to_connect().is_empty());
+        assert!(Topology::new().links_to_connect().is_empty());
+
+        let mut topo = create_line_topology();
+        topo.add_node(TopologyNode::new(n_1));
+        topo.add_node(TopologyNode::new(n_2));
+        let links = topo.links_to_connect();
+        assert_eq!(links, vec![(n_1, n_2), (n_2, n_a)]);
+
+        for (a_id, b_id) in links {
+            topo.connect_auto(a_id, b_id, InterfaceType::LocalNet).unwrap();
+        }
+        assert!(topo.is_connected());
+    }
+
+    #[test]
+    fn shared_segment() {
+        let (n_a, n_b, n_c, n_x, n_y) = (NodeId(0xA), NodeId(0xB), NodeId(0xC), NodeId(0x1), NodeId(0x2));
+        let if_3 = IfaceIndex(3);
+        let mut topo = create_line_topology();
+        topo.add_node(TopologyNode::new(n_x));
+        topo.add_node(TopologyNode::new(n_y));
+
+        assert_eq!(topo.add_segment(&[(n_a, if_3), (n_b, IfaceIndex(1))]), Err(TopologyError::InterfaceExists(n_b, IfaceIndex(1))));
+        assert_eq!(topo.add_segment(&[(n_a, if_3), (n_a, IfaceIndex(4))]), Err(TopologyError::DuplicateMember(n_a)));
+        assert_eq!(TopologyError::DuplicateMember(n_a).to_string(), "node A appears more than once on the segment");
+        assert!(!topo.get_node(n_a).ifaces.contains_key(&if_3));
+
+        topo.add_segment(&[(n_a, if_3), (n_c, if_3), (n_x, IfaceIndex(0))]).unwrap();
+        assert!(topo.same_segment(n_a, n_c) && topo.same_segment(n_x, n_a));
+        assert!(!topo.same_segment(n_a, n_b));
+        assert!(!topo.same_segment(n_a, n_y));
+        assert!(!topo.same_segment(n_y, n_a));
+
+        assert_eq!(path_ids(&topo.shortest_path(n_a, n_c).unwrap()), vec![n_a, n_c]);
+        assert_eq!(path_ids(&topo.shortest_path(n_b, n_x).unwrap()), vec![n_b, n_a, n_x]);
+        assert!(topo.asymmetric_links().is_empty());
+    }
+
+    #[test]
+    fn find_paths_filtered() {
+        let (n_c, n_d, n_e) = (NodeId(0xC), NodeId(0xD), NodeId(0xE));
+        let (if_0, if_2) = (IfaceIndex(0), IfaceIndex(2));
+        let topo = create_big_topology();
+
+        let all = topo.find_paths_filtered(n_d, if_0, n_c, if_2, |_| true).unwrap();
+        assert_eq!(all, topo.find_paths(n_d, if_0, n_c, if_2).unwrap());
+
+        let short: Vec<String> = topo.find_paths_filtered(n_d, if_0, n_c, if_2, |step| step.hops < 2)
+                                     .unwrap()
+                                     .iter()
+                                     .map(Path::to_string)
+                                     .collect();
+        assert_eq!(short, vec!["Path: (0)D(1) => (1)E(4) => (3)C(2)"]);
+
+        let avoid_e = |step: &StepContext| step.next != n_e;
+        assert_eq!(topo.find_paths_filtered(n_d, if_0, n_c, if_2, avoid_e), Err(PathError::NoRoute));
+        let cheap = topo.find_paths_filtered(n_d, if_0, n_c, if_2, |step| step.cost < 2).unwrap();
+        assert_eq!(cheap.iter().map(Path::to_string).collect::<Vec<_>>(), short);
+    }
+
+    #[test]
+    fn find_paths_with_progress() {
+        let (n_b, n_c, n_d) = (NodeId(0xB), NodeId(0xC), NodeId(0xD));
+        let (if_0, if_2) = (IfaceIndex(0), IfaceIndex(2));
+        let topo = create_big_topology();
+
+        let mut counts: Vec<usize> = Vec::new();
+        let paths = topo.find_paths_with_progress(n_d, if_0, n_c, if_2, 2, |count| counts.push(count)).unwrap();
+        assert_eq!(paths, topo.find_paths(n_d, if_0, n_c, if_2).unwrap());
+        assert_eq!(counts, vec![2, 4]);
+
+        counts.clear();
+        assert!(topo.find_paths_with_progress(n_d, if_0, n_c, if_2, 0, |count| counts.push(count)).is_ok());
+        assert_eq!(counts, vec![1, 2, 3, 4]);
+
+        assert_eq!(topo.find_paths_with_progress(n_d, if_0, n_b, IfaceIndex(9), 1, |_| ()),
+                   Err(PathError::FinishInterfaceInvalid(n_b, IfaceIndex(9))));
+    }
+
+    #[test]
+    fn path_via_any_gateway() {
+        let (n_a, n_b, n_d, n_e, n_f) = (NodeId(0xA), NodeId(0xB), NodeId(0xD), NodeId(0xE), NodeId(0xF));
+        let topo = create_big_topology();
+
+        let path = topo.path_via_any_gateway(n_d, n_f).unwrap();
+        assert_eq!(path.to_string(), "Path: (0)D(1) => (1)E(4) => (3)C(4) => (2)F(0)");
+        assert!(topo.is_valid_path(&path));
+
+        let hairpin = topo.path_via_any_gateway(n_d, n_e).unwrap();
+        assert_eq!(hairpin.to_string(), "Path: (0)D(1) => (1)E(4) => (3)C(3) => (4)E(0)");
+
+        assert_eq!(topo.path_via_any_gateway(n_a, n_b).unwrap().to_string(), "Path: (0)A(2) => (1)B(0)");
+        assert_eq!(create_line_topology().path_via_any_gateway(NodeId(0xA), NodeId(0xC)), None);
+    }
+
+    #[test]
+    fn clone_for_what_if() {
+        let (n_b, n_c, n_e) = (NodeId(0xB), NodeId(0xC), NodeId(0xE));
+        let topo = create_big_topology();
+
+        let mut what_if = topo.clone();
+        assert_eq!(what_if, topo);
+        what_if.set_admin_up(n_e, IfaceIndex(1), false).unwrap();
+        assert_ne!(what_if, topo);
+        assert_eq!(what_if.shortest_path(n_b, n_c).unwrap().nodes.len(), 2);
+        assert!(what_if.shortest_path(NodeId(0xD), n_c).is_none());
+        assert!(topo.shortest_path(NodeId(0xD), n_c).is_some());
+    }
+
+    #[test]
+    fn edge_disjoint_path_count() {
+        let (n_a, n_b, n_c, n_d, n_e) = (NodeId(0xA), NodeId(0xB), NodeId(0xC), NodeId(0xD), NodeId(0xE));
+        let mut topo = create_big_topology();
+
+        assert_eq!(topo.edge_disjoint_path_count(n_b, n_e), 3);
+        assert_eq!(topo.edge_disjoint_path_count(n_b, n_c), 3);
+        assert_eq!(topo.edge_disjoint_path_count(n_a, n_c), 1);
+        assert_eq!(topo.edge_disjoint_path_count(n_d, n_c), 1);
+        assert_eq!(topo.edge_disjoint_path_count(n_c, n_c), 0);
+        assert_eq!(topo.edge_disjoint_path_count(n_c, NodeId(0x1)), 0);
+
+        topo.set_admin_up(n_b, IfaceIndex(3), false).unwrap();
+        assert_eq!(topo.edge_disjoint_path_count(n_b, n_e), 2);
+    }
+
+    #[test]
+    fn best_gateway_placement() {
+        let (n_a, n_b, n_c) = (NodeId(0xA), NodeId(0xB), NodeId(0xC));
+        let mut topo = create_line_topology();
+        assert_eq!(topo.best_gateway_placement(), Some((n_b, 1)));
+
+        topo.get_node_mut(n_a).add_iface(Interface::new(IfaceIndex(7), InterfaceType::Internet, vec![]));
+        assert_eq!(topo.best_gateway_placement(), Some((n_b, 1)));
+
+        // D is two hops from C, and only a gateway on D or E brings everyone within one
+        assert_eq!(create_big_topology().best_gateway_placement(), Some((NodeId(0xD), 1)));
+
+        for id in [n_b, n_c] {
+            topo.get_node_mut(id).add_iface(Interface::new(IfaceIndex(7), InterfaceType::Internet, vec![]));
+        }
+        assert_eq!(topo.best_gateway_placement(), None);
+        assert_eq!(Topology::new().best_gateway_placement(), None);
+    }
+
+    #[test]
+    fn nearest_gateway() {
+        let (n_a, n_b, n_c, n_d) = (NodeId(0xA), NodeId(0xB), NodeId(0xC), NodeId(0xD));
+        let (if_1, if_2) = (IfaceIndex(1), IfaceIndex(2));
+        let mut topo = create_big_topology();
+
+        assert_eq!(topo.nearest_gateway(n_b), Some((n_a, if_1, 2)));
+        assert_eq!(topo.default_route(n_d).unwrap().to_string(), "Path: (0)D(1) => (1)E(4) => (3)C(2)");
+
+        // A is as close to B as C but its uplink now costs more
+        topo.get_node_mut(n_a).ifaces.get_mut(&if_1).unwrap().set_cost(5);
+        assert_eq!(topo.nearest_gateway(n_b), Some((n_c, if_2, 2)));
+        assert_eq!(topo.nearest_gateway(n_a), Some((n_c, if_2, 3)));
+        assert_eq!(topo.default_route(n_a).unwrap().to_string(), "Path: (0)A(2) => (1)B(4) => (1)C(2)");
+
+        topo.set_admin_up(n_c, if_2, false).unwrap();
+        assert_eq!(topo.nearest_gateway(n_d), Some((n_a, if_1, 8)));
+        assert_eq!(create_line_topology().nearest_gateway(n_a), None);
+        assert_eq!(create_line_topology().default_route(n_a), None);
+
+        // B reaches a gateway but has no app interface to route from
+        topo.get_node_mut(n_b).ifaces.remove(&IfaceIndex(0));
+        assert_eq!(topo.nearest_gateway(n_b), Some((n_a, if_1, 6)));
+        assert_eq!(topo.default_route(n_b), None);
+        assert_eq!(topo.default_route(NodeId(0x1)), None);
+    }
+
+    #[test]
+    fn clear() {
+        let mut topo = create_big_topology();
+        topo.clear();
+        assert_eq!(topo, Topology::new());
+        assert!(topo.find_internet_gateway().is_empty());
+
+        topo.add_node(TopologyNode::new(NodeId(0xA)));
+        assert_eq!(topo.stats().nodes, 1);
+    }
+
+    #[test]
+    fn path_min_transit() {
+        let (n_a, n_b, n_c, n_e, n_f) = (NodeId(0xA), NodeId(0xB), NodeId(0xC), NodeId(0xE), NodeId(0xF));
+        let topo = create_big_topology();
+
+        let path = topo.path_min_transit(n_b, n_f).unwrap();
+        assert_eq!(path.iter().map(PathNode::id).collect::<Vec<NodeId>>(), vec![n_b, n_e, n_f]);
+        assert_eq!(topo.path_min_transit(n_a, n_c).unwrap().iter().count(), 3);
+        assert_eq!(topo.path_min_transit(n_a, NodeId(0x1)), None);
+    }
+
+    #[test]
+    fn spanning_forest() {
+        let (n_a, n_b, n_c, n_d, n_e, n_f) =
+            (NodeId(0xA), NodeId(0xB), NodeId(0xC), NodeId(0xD), NodeId(0xE), NodeId(0xF));
+        let (if_1, if_2, if_3, if_4, if_5) = (IfaceIndex(1), IfaceIndex(2), IfaceIndex(3), IfaceIndex(4), IfaceIndex(5));
+
+        let mut topo = create_big_topology();
+        assert_eq!(topo.spanning_forest(), vec![
+            (n_a, if_2, n_b, if_1),
+            (n_b, if_2, n_e, if_2),
+            (n_b, if_4, n_c, if_1),
+            (n_d, if_1, n_e, if_1),
+            (n_e, if_5, n_f, if_1),
+        ]);
+
+        let mut line = create_line_topology();
+        line.add_node(TopologyNode::new(NodeId(0x1)));
+        assert_eq!(line.spanning_forest(), line.link_iface_report());
+
+        topo.set_admin_up(n_b, if_2, false).unwrap();
+        let forest = topo.spanning_forest();
+        assert_eq!(forest.len(), 5);
+        assert!(forest.contains(&(n_b, if_3, n_e, if_3)));
+        assert!(!forest.contains(&(n_b, if_2, n_e, if_2)));
+    }
+
+    #[test]
+    fn interface_builder() {
+        let (n_b, if_1, if_2) = (NodeId(0xB), IfaceIndex(1), IfaceIndex(2));
+
+        assert_eq!(Interface::builder(if_1).neighbor(n_b, if_2).neighbor(n_b, if_2).build(),
+                   Interface::new(if_1, InterfaceType::LocalNet, vec![(n_b, if_2)]));
+        assert_eq!(Interface::builder(if_2).internet().build(),
+                   Interface::new(if_2, InterfaceType::Internet, vec![]));
+        assert_eq!(Interface::builder(if_2).internet().local_app().build().iface_type(), InterfaceType::LocalApp);
+    }
+
+    #[test]
+    fn connectivity_after_failures() {
+        let (n_a, n_b, n_c, n_d, n_e, n_f) =
+            (NodeId(0xA), NodeId(0xB), NodeId(0xC), NodeId(0xD), NodeId(0xE), NodeId(0xF));
+        let topo = create_big_topology();
+
+        assert_eq!(topo.connectivity_after_failures(&[]), topo.connected_components());
+        assert_eq!(topo.connectivity_after_failures(&[n_c, NodeId(0x1)]), vec![vec![n_a, n_b, n_d, n_e, n_f]]);
+        assert_eq!(topo.connectivity_after_failures(&[n_b, n_e]), vec![vec![n_a], vec![n_c, n_f], vec![n_d]]);
+        assert_eq!(topo.connected_components().len(), 1);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serialize_deterministic() {
+        let topo = create_big_topology();
+        let mut shuffled = Topology::new();
+        let mut ids: Vec<NodeId> = topo.nodes.keys().copied().collect();
+        ids.sort_by_key(|&id| core::cmp::Reverse(id));
+        for id in ids {
+            let mut node = TopologyNode::new(id);
+            let mut ifaces: Vec<Interface> = topo.nodes[&id].ifaces.values().cloned().collect();
+            ifaces.sort_by_key(|iface| core::cmp::Reverse(iface.id));
+            for iface in ifaces {
+                node.add_iface(iface);
+            }
+            shuffled.add_node(node);
+        }
+        assert_eq!(shuffled, topo);
+
+        let json = serde_json::to_string(&topo).unwrap();
+        assert_eq!(serde_json::to_string(&shuffled).unwrap(), json);
+        assert!(json.starts_with(r#"{"nodes":[{"id":10,"interfaces":[{"index":0,"type":"local_app""#));
+
+        // labels don't count for `==`, so they don't show up either
+        let mut labeled = topo.clone();
+        labeled.get_node_mut(NodeId(0xA)).set_label("core-sw-1");
+        labeled.get_node_mut(NodeId(0xA)).ifaces.get_mut(&IfaceIndex(1)).unwrap().set_label("eth0");
+        assert_eq!(labeled, topo);
+        assert_eq!(serde_json::to_string(&labeled).unwrap(), json);
+    }
+
+    #[test]
+    fn cheapest_path_bounded_hops() {
+        let (n_a, n_b, n_c, n_d, n_e, n_f) =
+            (NodeId(0xA), NodeId(0xB), NodeId(0xC), NodeId(0xD), NodeId(0xE), NodeId(0xF));
+        let mut topo = create_big_topology();
+        // leaving E is pricey except towards F
+        for if_id in [IfaceIndex(2), IfaceIndex(3), IfaceIndex(4)] {
+            topo.get_node_mut(n_e).ifaces.get_mut(&if_id).unwrap().set_cost(10);
+        }
+
+        let (path, cost) = topo.cheapest_path_bounded_hops(n_d, n_c, 3).unwrap();
+        assert_eq!(path.iter().map(PathNode::id).collect::<Vec<NodeId>>(), vec![n_d, n_e, n_f, n_c]);
+        assert_eq!(cost, 3);
+        assert!(topo.is_valid_path(&path));
+
+        let (path, cost) = topo.cheapest_path_bounded_hops(n_d, n_c, 2).unwrap();
+        assert_eq!(path.to_string(), "Path: (0)D(1) => (1)E(4) => (3)C(0)");
+        assert_eq!(cost, 11);
+
+        assert_eq!(topo.cheapest_path_bounded_hops(n_d, n_a, 2), None);
+        assert_eq!(topo.cheapest_path_bounded_hops(n_d, n_a, 3).unwrap().1, 12);
+        assert_eq!(topo.cheapest_path_bounded_hops(n_d, n_a, 5).unwrap().1, 5);
+        assert_eq!(topo.cheapest_path_bounded_hops(n_b, n_b, 0).unwrap().1, 0);
+        assert_eq!(topo.cheapest_path_bounded_hops(n_b, NodeId(0x1), 5), None);
+    }
+
+    #[test]
+    fn find_paths_with_stats() {
+        let (n_a, n_c, n_d) = (NodeId(0xA), NodeId(0xC), NodeId(0xD));
+        let (if_0, if_2) = (IfaceIndex(0), IfaceIndex(2));
+        let topo = create_big_topology();
+
+        let (paths, stats) = topo.find_paths_with_stats(n_d, if_0, n_c, if_2);
+        assert_eq!(paths, topo.find_paths(n_d, if_0, n_c, if_2));
+        assert_eq!(stats.paths_found, 4);
+        // D-E-B-A is the deepest dead end
+        assert_eq!(stats.max_depth, 3);
+        assert!(stats.nodes_expanded > stats.paths_found);
+
+        assert_eq!(topo.find_paths_with_stats(n_a, if_0, n_a, if_0), (Err(PathError::NoRoute), SearchStats::default()));
+
+        // the search still reports how far it got before running dry
+        let mut line = create_line_topology();
+        line.set_admin_up(NodeId(0xB), if_2, false).unwrap();
+        assert_eq!(line.find_paths_with_stats(NodeId(0xA), if_0, NodeId(0xC), if_0),
+                   (Err(PathError::NoRoute), SearchStats { max_depth: 1, nodes_expanded: 2, paths_found: 0 }));
+        assert_eq!(line.find_paths_with_stats(NodeId(0x1), if_0, NodeId(0xC), if_0),
+                   (Err(PathError::StartNotFound(NodeId(0x1))), SearchStats::default()));
+    }
+
+    #[test]
+    fn are_adjacent() {
+        let (n_a, n_b, n_c) = (NodeId(0xA), NodeId(0xB), NodeId(0xC));
+        let mut topo = create_line_topology();
+
+        assert!(topo.are_adjacent(n_a, n_b));
+        assert!(topo.are_adjacent(n_b, n_a));
+        assert!(!topo.are_adjacent(n_a, n_c));
+        assert!(!topo.are_adjacent(n_a, n_a));
+        assert!(!topo.are_adjacent(NodeId(0x1), n_a));
+        assert!(!topo.are_adjacent(n_a, NodeId(0x1)));
+
+        topo.set_oper_up(n_b, IfaceIndex(2), false).unwrap();
+        assert!(!topo.are_adjacent(n_b, n_c));
+    }
+
+    #[test]
+    fn weighted_betweenness_centrality() {
+        let (n_a, n_b, n_c, n_d, n_e, n_f) =
+            (NodeId(0xA), NodeId(0xB), NodeId(0xC), NodeId(0xD), NodeId(0xE), NodeId(0xF));
+
+        let line = create_line_topology().weighted_betweenness_centrality();
+        assert_eq!(line, HashMap::from([(n_a, 0.0), (n_b, 1.0), (n_c, 0.0)]));
+
+        let mut topo = create_big_topology();
+        let scores = topo.weighted_betweenness_centrality();
+        assert_eq!(scores[&n_a], 0.0);
+        assert_eq!(scores[&n_b], 4.0);
+        assert!((scores[&n_c] - 2.0 / 3.0).abs() < 1e-9);
+        assert_eq!(scores[&n_d], 0.0);
+        assert!((scores[&n_e] - 16.0 / 3.0).abs() < 1e-9);
+        assert_eq!(scores[&n_f], 0.0);
+
+        // a pricey B-C link sends everything that used it through E instead
+        for (id, if_id) in [(n_b, IfaceIndex(4)), (n_c, IfaceIndex(1))] {
+            topo.get_node_mut(id).ifaces.get_mut(&if_id).unwrap().set_cost(5);
+        }
+        let weighted = topo.weighted_betweenness_centrality();
+        assert_eq!(weighted[&n_b], 4.0);
+        assert_eq!(weighted[&n_c], 0.0);
+        assert_eq!(weighted[&n_e], 8.0);
+
+        // free links count as cost 1, whichever end settles first
+        let mut free = create_big_topology();
+        for (id, if_id) in [(n_e, IfaceIndex(4)), (n_c, IfaceIndex(3)), (n_b, IfaceIndex(2)), (n_e, IfaceIndex(2))] {
+            free.get_node_mut(id).ifaces.get_mut(&if_id).unwrap().set_cost(0);
+        }
+        let free_scores = free.weighted_betweenness_centrality();
+        assert!(scores.iter().all(|(id, score)| (free_scores[id] - score).abs() < 1e-9));
+    }
+
+    #[test]
+    fn fixtures() {
+        assert_eq!(fixtures::line_topology(0), Topology::new());
+        assert_eq!(fixtures::line_topology(1).stats().links, 0);
+
+        let line = fixtures::line_topology(5);
+        assert!(line.asymmetric_links().is_empty());
+        assert_eq!(line.diameter(), Some(4));
+        assert_eq!(line.link_iface_report()[3], (NodeId(0xD), IfaceIndex(2), NodeId(0xE), IfaceIndex(1)));
+
+        let gw_line = fixtures::line_with_gateway(4);
+        assert_eq!(gw_line.find_internet_gateway(), vec![NodeId(0xD)]);
+        assert_eq!(gw_line.nearest_gateway(NodeId(0xA)), Some((NodeId(0xD), IfaceIndex(2), 4)));
+
+        let mesh = fixtures::sample_mesh();
+        assert!(mesh.asymmetric_links().is_empty() && mesh.dangling_neighbors().is_empty());
+        assert_eq!(mesh.stats().links, 8);
+    }
+
+    #[test]
+    fn best_service_node() {
+        let (n_a, n_b) = (NodeId(0xA), NodeId(0xB));
+
+        let mut line = create_line_topology();
+        assert_eq!(line.best_service_node(), Some((n_b, 2)));
+
+        // an isolated app doesn't pull the choice away from the line
+        line.add_node(TopologyNode::new(NodeId(0x1)));
+        line.get_node_mut(NodeId(0x1)).add_iface(Interface::builder(IfaceIndex(0)).local_app().build());
+        assert_eq!(line.best_service_node(), Some((n_b, 2)));
+
+        // reaching more apps beats being close to fewer: from 1 or 2 the
+        // two apps there add up to 1 hop, from B the three on the line to 2
+        line.add_node(TopologyNode::new(NodeId(0x2)));
+        line.get_node_mut(NodeId(0x2)).add_iface(Interface::builder(IfaceIndex(0)).local_app().build());
+        line.connect(NodeId(0x1), IfaceIndex(1), NodeId(0x2), IfaceIndex(1)).unwrap();
+        assert_eq!(line.distances_from(NodeId(0x1)).len(), 2);
+        assert_eq!(line.best_service_node(), Some((n_b, 2)));
+
+        // E is 1 hop from B, C, D and F and 2 from A
+        assert_eq!(create_big_topology().best_service_node(), Some((NodeId(0xE), 6)));
+
+        let mut bare = Topology::new();
+        bare.add_node(TopologyNode::new(n_a));
+        assert_eq!(bare.best_service_node(), None);
+        assert_eq!(fixtures::line_topology(2).best_service_node(), Some((n_a, 1)));
+    }
+
+    #[test]
+    fn find_paths_between() {
+        let (n_a, n_c, n_d) = (NodeId(0xA), NodeId(0xC), NodeId(0xD));
+        let mut topo = create_big_topology();
+
+        assert_eq!(topo.find_paths_between(n_d, n_c), topo.find_paths(n_d, IfaceIndex(0), n_c, IfaceIndex(0)));
+
+        topo.get_node_mut(n_c).add_iface(Interface::builder(IfaceIndex(7)).local_app().build());
+        assert!(topo.find_paths_between(n_a, n_c).unwrap().iter().all(|path| path.to_string().ends_with("C(0)")));
+
+        topo.add_node(TopologyNode::new(NodeId(0x1)));
+        assert_eq!(topo.find_paths_between(NodeId(0x1), n_c), Err(PathError::NoAppInterface(NodeId(0x1))));
+        assert_eq!(topo.find_paths_between(n_c, NodeId(0x1)), Err(PathError::NoAppInterface(NodeId(0x1))));
+        assert_eq!(topo.find_paths_between(NodeId(0x2), n_c), Err(PathError::StartNotFound(NodeId(0x2))));
+        assert_eq!(topo.find_paths_between(n_c, NodeId(0x2)), Err(PathError::FinishNotFound(NodeId(0x2))));
+    }
 }
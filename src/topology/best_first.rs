@@ -0,0 +1,153 @@
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+
+use super::IfaceIndex;
+use super::NodeId;
+use super::Path;
+use super::PathError;
+use super::PathNode;
+use super::Topology;
+use super::collections::BinaryHeap;
+
+// Partial path on the frontier. Ordered so that `BinaryHeap` pops the fewest
+// hops first and, among equals, the one pushed first.
+struct Candidate {
+    hops: usize,
+    seq: u64,
+    path: Path,
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        (self.hops, self.seq) == (other.hops, other.seq)
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (other.hops, other.seq).cmp(&(self.hops, self.seq))
+    }
+}
+
+struct BestFirstPaths<'a> {
+    topo: &'a Topology,
+    finish_id: NodeId,
+    finish_if_id: IfaceIndex,
+    frontier: BinaryHeap<Candidate>,
+    pushed: u64,
+}
+
+impl BestFirstPaths<'_> {
+    fn push(&mut self, hops: usize, path: Path) {
+        self.frontier.push(Candidate { hops, seq: self.pushed, path });
+        self.pushed += 1;
+    }
+}
+
+impl Iterator for BestFirstPaths<'_> {
+    type Item = Path;
+
+    fn next(&mut self) -> Option<Path> {
+        while let Some(Candidate { hops, mut path, .. }) = self.frontier.pop() {
+            let last_id = path.nodes.back().unwrap().id;
+
+            if last_id == self.finish_id {
+                path.nodes.back_mut().unwrap().forward_if_id = self.finish_if_id;
+                return Some(path);
+            }
+
+            for (if_id, neigh_id, neigh_if_id) in self.topo.neighbors(last_id) {
+                if path.nodes.iter().any(|x| x.id == neigh_id) {
+                    continue;
+                }
+
+                let mut next_path = path.clone();
+                next_path.nodes.back_mut().unwrap().forward_if_id = if_id;
+                let mut path_node = PathNode::new(neigh_id);
+                path_node.reverse_if_id = neigh_if_id;
+                next_path.nodes.push_back(path_node);
+                self.push(hops + 1, next_path);
+            }
+        }
+        None
+    }
+}
+
+impl Topology {
+    /// Lazily yields the loop-free `LocalNet` paths in increasing hop count,
+    /// ties in discovery order, so `take(k)` gives the k shortest routes.
+    /// Nothing is yielded when both ends are the same node and interface.
+    ///
+    /// Every partial path still worth extending is kept on the frontier, so
+    /// memory grows with the number of partial paths shorter than the one
+    /// being yielded. On dense graphs that is exponential in the hop count;
+    /// only pull as many paths as needed.
+    pub fn paths_best_first(&self,
+                            start_id: NodeId,
+                            start_if_id: IfaceIndex,
+                            finish_id: NodeId,
+                            finish_if_id: IfaceIndex) -> impl Iterator<Item = Path> + '_ {
+        let mut paths = BestFirstPaths {
+            topo: self,
+            finish_id,
+            finish_if_id,
+            frontier: BinaryHeap::new(),
+            pushed: 0,
+        };
+
+        // as in `find_path`, a node isn't a path to itself through the same
+        // interface
+        if self.nodes.contains_key(&start_id) && (start_id != finish_id || start_if_id != finish_if_id) {
+            let mut path = Path::new();
+            let mut path_node = PathNode::new(start_id);
+            path_node.reverse_if_id = start_if_id;
+            path.nodes.push_back(path_node);
+            paths.push(0, path);
+        }
+        paths
+    }
+
+    /// At most `limit` loop-free paths, the ones with the fewest hops first.
+    /// Endpoints are checked as in `find_paths`; a `limit` of 0 gives an
+    /// empty `Ok`.
+    pub fn find_paths_limited(&self,
+                              start_id: NodeId,
+                              start_if_id: IfaceIndex,
+                              finish_id: NodeId,
+                              finish_if_id: IfaceIndex,
+                              limit: usize) -> Result<Vec<Path>, PathError> {
+        self.check_endpoints(start_id, start_if_id, finish_id, finish_if_id)?;
+
+        let paths: Vec<Path> = self.paths_best_first(start_id, start_if_id, finish_id, finish_if_id)
+                                   .take(limit)
+                                   .collect();
+        if paths.is_empty() && limit > 0 {
+            return Err(PathError::NoRoute);
+        }
+        Ok(paths)
+    }
+
+    /// The only loop-free path between the endpoints, or
+    /// `PathError::Ambiguous` if there are more. Finding out takes just two
+    /// paths however many there are, so the count in `Ambiguous` is always
+    /// 2, a lower bound.
+    pub fn unique_path(&self,
+                       start_id: NodeId,
+                       start_if_id: IfaceIndex,
+                       finish_id: NodeId,
+                       finish_if_id: IfaceIndex) -> Result<Path, PathError> {
+        let mut paths = self.find_paths_limited(start_id, start_if_id, finish_id, finish_if_id, 2)?;
+        match paths.len() {
+            1 => Ok(paths.pop().unwrap()),
+            count => Err(PathError::Ambiguous(count)),
+        }
+    }
+}
@@ -0,0 +1,145 @@
+use alloc::vec::Vec;
+use alloc::vec;
+
+use super::IfaceIndex;
+use super::Interface;
+use super::InterfaceType;
+use super::NodeId;
+use super::Topology;
+use super::TopologyError;
+use super::TopologyNode;
+use super::collections::HashSet;
+
+/// Spells out an `Interface` one property at a time, see
+/// `Interface::builder`. The type is `LocalNet` unless set otherwise.
+#[derive(Debug)]
+pub struct InterfaceBuilder {
+    id: IfaceIndex,
+    if_type: InterfaceType,
+    neighbors: Vec<(NodeId, IfaceIndex)>,
+}
+
+impl InterfaceBuilder {
+    pub fn local_app(mut self) -> Self {
+        self.if_type = InterfaceType::LocalApp;
+        self
+    }
+
+    pub fn local_net(mut self) -> Self {
+        self.if_type = InterfaceType::LocalNet;
+        self
+    }
+
+    pub fn internet(mut self) -> Self {
+        self.if_type = InterfaceType::Internet;
+        self
+    }
+
+    pub fn shared_medium(mut self) -> Self {
+        self.if_type = InterfaceType::SharedMedium;
+        self
+    }
+
+    /// Adds interface `iface` of `node` as a neighbor, unless it is listed
+    /// already.
+    pub fn neighbor(mut self, node: NodeId, iface: IfaceIndex) -> Self {
+        if !self.neighbors.contains(&(node, iface)) {
+            self.neighbors.push((node, iface));
+        }
+        self
+    }
+
+    pub fn build(self) -> Interface {
+        Interface::new(self.id, self.if_type, self.neighbors)
+    }
+}
+
+impl Interface {
+    /// Starts an `InterfaceBuilder` for interface `id`, an alternative to
+    /// `new` that names the type instead of passing it positionally.
+    pub fn builder(id: IfaceIndex) -> InterfaceBuilder {
+        InterfaceBuilder { id, if_type: InterfaceType::LocalNet, neighbors: Vec::new() }
+    }
+}
+
+/// Collects node and interface declarations and turns them into a
+/// `Topology` in one go. Nothing is checked until `build`, which reports
+/// every problem at once instead of stopping at the first.
+#[derive(Debug, Default)]
+pub struct TopologyBuilder {
+    nodes: Vec<NodeId>,
+    ifaces: Vec<(NodeId, Interface)>,
+}
+
+impl TopologyBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Declares a node. Declaring the same node twice is harmless.
+    pub fn node(mut self, id: NodeId) -> Self {
+        self.nodes.push(id);
+        self
+    }
+
+    /// Links `a_id` and `b_id` with a `LocalNet` interface on each side.
+    pub fn link(mut self, a_id: NodeId, a_if_id: IfaceIndex, b_id: NodeId, b_if_id: IfaceIndex) -> Self {
+        self.ifaces.push((a_id, Interface::new(a_if_id, InterfaceType::LocalNet, vec![(b_id, b_if_id)])));
+        self.ifaces.push((b_id, Interface::new(b_if_id, InterfaceType::LocalNet, vec![(a_id, a_if_id)])));
+        self
+    }
+
+    /// Gives `id` an `Internet` interface.
+    pub fn gateway(mut self, id: NodeId, if_id: IfaceIndex) -> Self {
+        self.ifaces.push((id, Interface::new(if_id, InterfaceType::Internet, vec![])));
+        self
+    }
+
+    /// Gives `id` a `LocalApp` interface.
+    pub fn app(mut self, id: NodeId, if_id: IfaceIndex) -> Self {
+        self.ifaces.push((id, Interface::new(if_id, InterfaceType::LocalApp, vec![])));
+        self
+    }
+
+    /// Assembles the topology, or returns every error found: interfaces on
+    /// undeclared nodes, duplicate interface indices (the first declaration
+    /// wins), self-loops, dangling neighbors and links only one side knows
+    /// about.
+    pub fn build(self) -> Result<Topology, Vec<TopologyError>> {
+        let mut errors: Vec<TopologyError> = Vec::new();
+        let mut topo = Topology::new();
+
+        for id in self.nodes {
+            topo.nodes.entry(id).or_insert_with(|| TopologyNode::new(id));
+        }
+
+        let mut missing: HashSet<NodeId> = HashSet::new();
+        for (id, iface) in self.ifaces {
+            match topo.nodes.get_mut(&id) {
+                Some(node) => {
+                    if let Err(err) = node.try_add_iface(iface) {
+                        errors.push(err);
+                    }
+                }
+                None => {
+                    if missing.insert(id) {
+                        errors.push(TopologyError::NodeNotFound(id));
+                    }
+                }
+            }
+        }
+
+        errors.extend(topo.dangling_neighbors()
+                          .into_iter()
+                          .map(|(id, if_id, neigh_id)| TopologyError::DanglingNeighbor(id, if_id, neigh_id)));
+        errors.extend(topo.asymmetric_links()
+                          .into_iter()
+                          .map(|(id, if_id, neigh_id, neigh_if_id)| TopologyError::AsymmetricLink(id, if_id, neigh_id, neigh_if_id)));
+
+        if errors.is_empty() {
+            Ok(topo)
+        } else {
+            Err(errors)
+        }
+    }
+}
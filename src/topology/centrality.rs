@@ -0,0 +1,154 @@
+use alloc::vec::Vec;
+use alloc::vec;
+use core::cmp::Reverse;
+
+use super::IfaceIndex;
+use super::NodeId;
+use super::Topology;
+use super::collections::BinaryHeap;
+use super::collections::HashMap;
+use super::collections::HashSet;
+use super::collections::VecDeque;
+
+type Link = (NodeId, IfaceIndex, NodeId, IfaceIndex);
+
+// `(a, a_if, b, b_if)` oriented from the smaller end, as in `canonical_links`.
+fn canonical(a_id: NodeId, a_if_id: IfaceIndex, b_id: NodeId, b_if_id: IfaceIndex) -> Link {
+    if (a_id, a_if_id) <= (b_id, b_if_id) {
+        (a_id, a_if_id, b_id, b_if_id)
+    } else {
+        (b_id, b_if_id, a_id, a_if_id)
+    }
+}
+
+impl Topology {
+    /// For every usable `LocalNet` link, keyed from its smaller
+    /// `(node, interface)` end, the number of shortest paths between node
+    /// pairs that cross it. A pair with several shortest paths spreads its
+    /// share evenly over them, and parallel links count as separate paths.
+    /// Each unordered pair counts once; pairs in different components have
+    /// no path and contribute nothing.
+    ///
+    /// This is Brandes' algorithm with hop counts: one BFS per node, so
+    /// O(V·E) overall.
+    pub fn edge_betweenness(&self) -> HashMap<Link, f64> {
+        let mut scores: HashMap<Link, f64> = HashMap::new();
+        for &id in self.nodes.keys() {
+            for (if_id, neigh_id, neigh_if_id) in self.neighbors(id) {
+                scores.insert(canonical(id, if_id, neigh_id, neigh_if_id), 0.0);
+            }
+        }
+
+        for &source in self.nodes.keys() {
+            let mut order: Vec<NodeId> = Vec::new();
+            let mut dist: HashMap<NodeId, usize> = HashMap::from([(source, 0)]);
+            let mut sigma: HashMap<NodeId, f64> = HashMap::from([(source, 1.0)]);
+            let mut preds: HashMap<NodeId, Vec<(NodeId, IfaceIndex, IfaceIndex)>> = HashMap::new();
+            let mut queue: VecDeque<NodeId> = VecDeque::from([source]);
+
+            while let Some(id) = queue.pop_front() {
+                order.push(id);
+                let (id_dist, id_sigma) = (dist[&id], sigma[&id]);
+                for (if_id, neigh_id, neigh_if_id) in self.neighbors(id) {
+                    let neigh_dist = *dist.entry(neigh_id).or_insert_with(|| {
+                        queue.push_back(neigh_id);
+                        id_dist + 1
+                    });
+                    if neigh_dist == id_dist + 1 {
+                        *sigma.entry(neigh_id).or_insert(0.0) += id_sigma;
+                        preds.entry(neigh_id).or_default().push((id, if_id, neigh_if_id));
+                    }
+                }
+            }
+
+            // dependency of `source` on each node, accumulated farthest first
+            let mut delta: HashMap<NodeId, f64> = HashMap::new();
+            for id in order.into_iter().rev() {
+                let share = (1.0 + delta.get(&id).copied().unwrap_or(0.0)) / sigma[&id];
+                for &(pred_id, pred_if_id, if_id) in preds.get(&id).into_iter().flatten() {
+                    let credit = sigma[&pred_id] * share;
+                    *scores.entry(canonical(pred_id, pred_if_id, id, if_id)).or_insert(0.0) += credit;
+                    *delta.entry(pred_id).or_insert(0.0) += credit;
+                }
+            }
+        }
+
+        // every pair was counted once from each end
+        for score in scores.values_mut() {
+            *score /= 2.0;
+        }
+        scores
+    }
+
+    /// For every node, the number of cheapest paths between pairs of other
+    /// nodes that pass through it, by the summed `cost` of their links. A
+    /// pair with several cheapest paths spreads its share evenly over them.
+    /// Each unordered pair counts once, and pairs in different components
+    /// contribute nothing. With equal costs everywhere this is the usual
+    /// hop-count betweenness.
+    ///
+    /// A link of cost 0 counts as cost 1 here. Free links would let paths of
+    /// equal cost differ in length, and counting cheapest paths one
+    /// settled node at a time then depends on the order ties are settled.
+    ///
+    /// This is Brandes' algorithm with one Dijkstra run per node, so
+    /// O(V·E·log V) overall.
+    pub fn weighted_betweenness_centrality(&self) -> HashMap<NodeId, f64> {
+        let mut scores: HashMap<NodeId, f64> = self.nodes.keys().map(|&id| (id, 0.0)).collect();
+
+        for &source in self.nodes.keys() {
+            let mut order: Vec<NodeId> = Vec::new();
+            let mut settled: HashSet<NodeId> = HashSet::new();
+            let mut dist: HashMap<NodeId, u32> = HashMap::from([(source, 0)]);
+            let mut sigma: HashMap<NodeId, f64> = HashMap::from([(source, 1.0)]);
+            let mut preds: HashMap<NodeId, Vec<NodeId>> = HashMap::new();
+            let mut heap: BinaryHeap<Reverse<(u32, NodeId)>> = BinaryHeap::from([Reverse((0, source))]);
+
+            while let Some(Reverse((id_dist, id))) = heap.pop() {
+                if !settled.insert(id) {
+                    continue;
+                }
+                order.push(id);
+                let id_sigma = sigma[&id];
+                for (if_id, neigh_id, _) in self.neighbors(id) {
+                    if settled.contains(&neigh_id) {
+                        continue;
+                    }
+                    let neigh_dist = id_dist.saturating_add(self.link_cost(id, if_id).max(1));
+                    match dist.get(&neigh_id) {
+                        Some(&best) if neigh_dist > best => {}
+                        Some(&best) if neigh_dist == best => {
+                            *sigma.get_mut(&neigh_id).unwrap() += id_sigma;
+                            preds.get_mut(&neigh_id).unwrap().push(id);
+                        }
+                        _ => {
+                            dist.insert(neigh_id, neigh_dist);
+                            sigma.insert(neigh_id, id_sigma);
+                            preds.insert(neigh_id, vec![id]);
+                            heap.push(Reverse((neigh_dist, neigh_id)));
+                        }
+                    }
+                }
+            }
+
+            // dependency of `source` on each node, accumulated farthest first
+            let mut delta: HashMap<NodeId, f64> = HashMap::new();
+            for id in order.into_iter().rev() {
+                let id_delta = delta.get(&id).copied().unwrap_or(0.0);
+                let share = (1.0 + id_delta) / sigma[&id];
+                for &pred_id in preds.get(&id).into_iter().flatten() {
+                    *delta.entry(pred_id).or_insert(0.0) += sigma[&pred_id] * share;
+                }
+                if id != source {
+                    *scores.get_mut(&id).unwrap() += id_delta;
+                }
+            }
+        }
+
+        // every pair was counted once from each end
+        for score in scores.values_mut() {
+            *score /= 2.0;
+        }
+        scores
+    }
+}
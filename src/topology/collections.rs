@@ -0,0 +1,17 @@
+// Map and set types used throughout the crate. Without the `std` feature
+// there is no hasher to build `HashMap` on, so the ordered `alloc` maps
+// stand in under the same names; every key type is `Ord` for this.
+#[cfg(feature = "std")]
+pub use std::collections::HashMap;
+#[cfg(feature = "std")]
+pub use std::collections::HashSet;
+
+#[cfg(not(feature = "std"))]
+pub use alloc::collections::BTreeMap as HashMap;
+#[cfg(not(feature = "std"))]
+pub use alloc::collections::BTreeSet as HashSet;
+
+pub use alloc::collections::BTreeMap;
+pub use alloc::collections::BTreeSet;
+pub use alloc::collections::BinaryHeap;
+pub use alloc::collections::VecDeque;
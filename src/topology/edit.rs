@@ -0,0 +1,204 @@
+use alloc::vec::Vec;
+
+use super::IfaceIndex;
+use super::Interface;
+use super::InterfaceType;
+use super::NodeId;
+use super::Topology;
+use super::TopologyError;
+use super::TopologyNode;
+use super::collections::HashMap;
+
+impl Topology {
+    /// Creates `new_id` and moves the `move_ifaces` interfaces of `id` over to
+    /// it, keeping their indices and rewriting the neighbor entries of their
+    /// peers to point at `new_id`. The two halves are not linked; see
+    /// `split_node_linked`. Nothing changes on error.
+    pub fn split_node(&mut self, id: NodeId, new_id: NodeId, move_ifaces: &[IfaceIndex]) -> Result<(), TopologyError> {
+        let node = self.nodes.get(&id).ok_or(TopologyError::NodeNotFound(id))?;
+        if self.nodes.contains_key(&new_id) {
+            return Err(TopologyError::NodeExists(new_id));
+        }
+        if let Some(&if_id) = move_ifaces.iter().find(|if_id| !node.ifaces.contains_key(if_id)) {
+            return Err(TopologyError::InterfaceNotFound(id, if_id));
+        }
+
+        let mut new_node = TopologyNode::new(new_id);
+        for if_id in move_ifaces {
+            if let Some(iface) = self.get_node_mut(id).ifaces.remove(if_id) {
+                new_node.add_iface(iface);
+            }
+        }
+
+        let renamed: HashMap<(NodeId, IfaceIndex), (NodeId, IfaceIndex)> =
+            new_node.ifaces
+                    .keys()
+                    .map(|&if_id| ((id, if_id), (new_id, if_id)))
+                    .collect();
+        let peers: Vec<(NodeId, IfaceIndex)> = new_node.ifaces
+                                                       .values()
+                                                       .flat_map(|iface| iface.neighbors.iter().copied())
+                                                       .collect();
+        self.add_node(new_node);
+        self.rename_neighbor_entries(&peers, &renamed);
+        Ok(())
+    }
+
+    /// `split_node`, then links `id` and `new_id` with a new `LocalNet`
+    /// interface on each side, taking the smallest free indices. Returns the
+    /// indices picked on `id` and `new_id`.
+    pub fn split_node_linked(&mut self,
+                             id: NodeId,
+                             new_id: NodeId,
+                             move_ifaces: &[IfaceIndex]) -> Result<(IfaceIndex, IfaceIndex), TopologyError> {
+        self.split_node(id, new_id, move_ifaces)?;
+        self.connect_auto(id, new_id, InterfaceType::LocalNet)
+    }
+
+    /// Collapses `b_id` into its neighbor `a_id`: every `a`–`b` link is
+    /// dropped, the rest of `b`'s interfaces move to `a` with their peers
+    /// pointing at `a`, and `b` is removed. A moved interface whose index is
+    /// taken on `a` gets the smallest free one instead. Nothing changes on
+    /// error.
+    pub fn contract_edge(&mut self, a_id: NodeId, b_id: NodeId) -> Result<(), TopologyError> {
+        for id in [a_id, b_id] {
+            if !self.nodes.contains_key(&id) {
+                return Err(TopologyError::NodeNotFound(id));
+            }
+        }
+        if a_id == b_id || !self.are_adjacent(a_id, b_id) {
+            return Err(TopologyError::NotAdjacent(a_id, b_id));
+        }
+
+        // Interfaces that only served the a-b links go away on both sides.
+        let only_links_to = |iface: &Interface, id: NodeId| {
+            !iface.neighbors.is_empty() && iface.neighbors.iter().all(|(neigh_id, _)| *neigh_id == id)
+        };
+        let a_dropped: Vec<IfaceIndex> = self.nodes[&a_id].ifaces
+                                                           .values()
+                                                           .filter(|iface| only_links_to(iface, b_id))
+                                                           .map(|iface| iface.id)
+                                                           .collect();
+        let mut b_moved: Vec<IfaceIndex> = self.nodes[&b_id].ifaces
+                                                            .values()
+                                                            .filter(|iface| !only_links_to(iface, a_id))
+                                                            .map(|iface| iface.id)
+                                                            .collect();
+        b_moved.sort();
+
+        let mut used: Vec<IfaceIndex> = self.nodes[&a_id].ifaces
+                                                         .keys()
+                                                         .filter(|if_id| !a_dropped.contains(if_id))
+                                                         .copied()
+                                                         .collect();
+        let mut renamed: HashMap<(NodeId, IfaceIndex), (NodeId, IfaceIndex)> = HashMap::new();
+        for &if_id in &b_moved {
+            let new_if_id = if !used.contains(&if_id) {
+                if_id
+            } else {
+                (0..=u8::MAX).map(IfaceIndex)
+                             .find(|x| !used.contains(x) && !b_moved.contains(x))
+                             .ok_or(TopologyError::InterfacesExhausted(a_id))?
+            };
+            used.push(new_if_id);
+            renamed.insert((b_id, if_id), (a_id, new_if_id));
+        }
+
+        let mut b_node = self.nodes.remove(&b_id).unwrap();
+        let a_node = self.get_node_mut(a_id);
+        for if_id in &a_dropped {
+            a_node.ifaces.remove(if_id);
+        }
+        for iface in a_node.ifaces.values_mut() {
+            iface.neighbors.retain(|(neigh_id, _)| *neigh_id != b_id);
+        }
+
+        let mut peers: Vec<(NodeId, IfaceIndex)> = Vec::new();
+        for if_id in &b_moved {
+            let mut iface = b_node.ifaces.remove(if_id).unwrap();
+            iface.neighbors.retain(|(neigh_id, _)| *neigh_id != a_id);
+            iface.id = renamed[&(b_id, *if_id)].1;
+            peers.extend(iface.neighbors.iter().copied());
+            a_node.add_iface(iface);
+        }
+        self.rename_neighbor_entries(&peers, &renamed);
+        Ok(())
+    }
+
+    /// Puts the topology into a normal form so that two structurally
+    /// identical topologies compare equal link by link: every neighbor list
+    /// is sorted by `(node, interface)` and duplicate entries are removed.
+    /// Nothing else changes; neighbors that point at missing nodes or
+    /// interfaces are kept, and interface indices stay as they are unless
+    /// `renumber_interfaces` is called as well.
+    pub fn canonicalize(&mut self) {
+        for node in self.nodes.values_mut() {
+            for iface in node.ifaces.values_mut() {
+                iface.neighbors.sort();
+                iface.neighbors.dedup();
+            }
+        }
+    }
+
+    /// Renumbers the interfaces of every node to `0..n`, ordered by type
+    /// (`LocalApp`, `LocalNet`, `SharedMedium`, `Internet`), then by the
+    /// sorted ids of the nodes they link to, then by their old index, and
+    /// rewrites the neighbor entries pointing at them. The result is
+    /// canonicalized.
+    pub fn renumber_interfaces(&mut self) {
+        let type_rank = |if_type: InterfaceType| match if_type {
+            InterfaceType::LocalApp => 0,
+            InterfaceType::LocalNet => 1,
+            InterfaceType::SharedMedium => 2,
+            InterfaceType::Internet => 3,
+        };
+
+        let mut renamed: HashMap<(NodeId, IfaceIndex), (NodeId, IfaceIndex)> = HashMap::new();
+        for node in self.nodes.values() {
+            let mut order: Vec<(u8, Vec<NodeId>, IfaceIndex)> =
+                node.ifaces
+                    .values()
+                    .map(|iface| {
+                        let mut neigh_ids: Vec<NodeId> = iface.neighbors.iter().map(|&(neigh_id, _)| neigh_id).collect();
+                        neigh_ids.sort();
+                        (type_rank(iface.if_type), neigh_ids, iface.id)
+                    })
+                    .collect();
+            order.sort();
+            for (new_if_id, (_, _, if_id)) in order.into_iter().enumerate() {
+                renamed.insert((node.id, if_id), (node.id, IfaceIndex(new_if_id as u8)));
+            }
+        }
+
+        for node in self.nodes.values_mut() {
+            for mut iface in core::mem::take(&mut node.ifaces).into_values() {
+                iface.id = renamed[&(node.id, iface.id)].1;
+                for neighbor in iface.neighbors.iter_mut() {
+                    if let Some(&new_neighbor) = renamed.get(neighbor) {
+                        *neighbor = new_neighbor;
+                    }
+                }
+                node.ifaces.insert(iface.id, iface);
+            }
+        }
+        self.canonicalize();
+    }
+
+    // Rewrites the neighbor entries found on the `peers` interfaces according
+    // to `renamed`.
+    fn rename_neighbor_entries(&mut self,
+                               peers: &[(NodeId, IfaceIndex)],
+                               renamed: &HashMap<(NodeId, IfaceIndex), (NodeId, IfaceIndex)>) {
+        for (peer_id, peer_if_id) in peers {
+            let peer_iface = match self.nodes.get_mut(peer_id).and_then(|node| node.ifaces.get_mut(peer_if_id)) {
+                Some(peer_iface) => peer_iface,
+                None => continue,
+            };
+            for neighbor in peer_iface.neighbors.iter_mut() {
+                if let Some(&new_neighbor) = renamed.get(neighbor) {
+                    *neighbor = new_neighbor;
+                }
+            }
+        }
+    }
+}
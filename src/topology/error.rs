@@ -0,0 +1,76 @@
+use core::fmt::Display;
+
+use super::IfaceIndex;
+use super::NodeId;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TopologyError {
+    NodeNotFound(NodeId),
+    NodeExists(NodeId),
+    InterfaceNotFound(NodeId, IfaceIndex),
+    InterfaceExists(NodeId, IfaceIndex),
+    SelfLoop(NodeId, IfaceIndex),
+    InterfacesExhausted(NodeId),
+    /// The two nodes share no `LocalNet` link.
+    NotAdjacent(NodeId, NodeId),
+    /// An interface lists a neighbor node that isn't in the topology.
+    DanglingNeighbor(NodeId, IfaceIndex, NodeId),
+    /// An interface lists a neighbor interface that doesn't list it back.
+    AsymmetricLink(NodeId, IfaceIndex, NodeId, IfaceIndex),
+    /// A node appears more than once among the members of a segment.
+    DuplicateMember(NodeId),
+}
+
+impl Display for TopologyError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            TopologyError::NodeNotFound(id) => f.write_fmt(format_args!("node {} not found", id)),
+            TopologyError::NodeExists(id) => f.write_fmt(format_args!("node {} already exists", id)),
+            TopologyError::InterfaceNotFound(id, if_id) => f.write_fmt(format_args!("node {} has no interface {}", id, if_id)),
+            TopologyError::InterfaceExists(id, if_id) => f.write_fmt(format_args!("node {} already has interface {}", id, if_id)),
+            TopologyError::SelfLoop(id, if_id) => f.write_fmt(format_args!("interface {} of node {} links to its own node", if_id, id)),
+            TopologyError::InterfacesExhausted(id) => f.write_fmt(format_args!("node {} has no free interface index left", id)),
+            TopologyError::NotAdjacent(a_id, b_id) => f.write_fmt(format_args!("nodes {} and {} are not linked", a_id, b_id)),
+            TopologyError::DanglingNeighbor(id, if_id, neigh_id) => f.write_fmt(format_args!("interface {} of node {} lists unknown node {}", if_id, id, neigh_id)),
+            TopologyError::AsymmetricLink(id, if_id, neigh_id, neigh_if_id) => f.write_fmt(format_args!("interface {} of node {} lists interface {} of node {}, which doesn't list it back", if_id, id, neigh_if_id, neigh_id)),
+            TopologyError::DuplicateMember(id) => f.write_fmt(format_args!("node {} appears more than once on the segment", id)),
+        }
+    }
+}
+
+impl core::error::Error for TopologyError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathError {
+    StartNotFound(NodeId),
+    FinishNotFound(NodeId),
+    /// The start node has no such interface.
+    StartInterfaceInvalid(NodeId, IfaceIndex),
+    /// The finish node has no such interface.
+    FinishInterfaceInvalid(NodeId, IfaceIndex),
+    /// An endpoint that had to be entered or left through its `LocalApp`
+    /// interface has none.
+    NoAppInterface(NodeId),
+    /// The endpoints are valid but no path joins them.
+    NoRoute,
+    /// More than one path exists. The count is how many paths the search
+    /// found before it stopped looking, so only a lower bound: `unique_path`
+    /// stops at 2.
+    Ambiguous(usize),
+}
+
+impl Display for PathError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            PathError::StartNotFound(id) => f.write_fmt(format_args!("start node {} not found", id)),
+            PathError::FinishNotFound(id) => f.write_fmt(format_args!("finish node {} not found", id)),
+            PathError::StartInterfaceInvalid(id, if_id) => f.write_fmt(format_args!("start node {} has no interface {}", id, if_id)),
+            PathError::FinishInterfaceInvalid(id, if_id) => f.write_fmt(format_args!("finish node {} has no interface {}", id, if_id)),
+            PathError::NoAppInterface(id) => f.write_fmt(format_args!("node {} has no LocalApp interface", id)),
+            PathError::NoRoute => f.write_fmt(format_args!("no route found")),
+            PathError::Ambiguous(count) => f.write_fmt(format_args!("at least {} paths found where one was expected", count)),
+        }
+    }
+}
+
+impl core::error::Error for PathError {}
@@ -0,0 +1,178 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use alloc::vec;
+use core::fmt::Write;
+
+use super::IfaceIndex;
+use super::InterfaceType;
+use super::NodeId;
+use super::Topology;
+use super::collections::BTreeSet;
+use super::collections::HashSet;
+use super::collections::VecDeque;
+
+impl Topology {
+    // Every LocalNet link once, up or down, oriented from the smaller
+    // (node, iface) end.
+    pub(super) fn canonical_links(&self) -> BTreeSet<(NodeId, IfaceIndex, NodeId, IfaceIndex)> {
+        let mut links = BTreeSet::new();
+
+        for &id in self.nodes.keys() {
+            for (if_id, neigh_id, neigh_if_id) in self.local_links(id) {
+                if (id, if_id) <= (neigh_id, neigh_if_id) {
+                    links.insert((id, if_id, neigh_id, neigh_if_id));
+                } else {
+                    links.insert((neigh_id, neigh_if_id, id, if_id));
+                }
+            }
+        }
+        links
+    }
+
+    /// Every `LocalNet` link once as `(a, a_iface, b, b_iface)`, oriented
+    /// from the smaller `(node, interface)` end and sorted. Links that are
+    /// down are included. Diffing two reports shows re-cabled links.
+    pub fn link_iface_report(&self) -> Vec<(NodeId, IfaceIndex, NodeId, IfaceIndex)> {
+        self.canonical_links().into_iter().collect()
+    }
+
+    /// Links of a BFS spanning tree of every connected component, each rooted
+    /// at the component's smallest id, in the same form and order as
+    /// `link_iface_report`. Links missing from it are the redundant ones.
+    /// Only usable links are followed.
+    pub fn spanning_forest(&self) -> Vec<(NodeId, IfaceIndex, NodeId, IfaceIndex)> {
+        let mut ids: Vec<NodeId> = self.nodes.keys().copied().collect();
+        ids.sort();
+
+        let mut seen: HashSet<NodeId> = HashSet::new();
+        let mut links: Vec<(NodeId, IfaceIndex, NodeId, IfaceIndex)> = Vec::new();
+        for root in ids {
+            if !seen.insert(root) {
+                continue;
+            }
+
+            let mut queue: VecDeque<NodeId> = VecDeque::from([root]);
+            while let Some(id) = queue.pop_front() {
+                for (if_id, neigh_id, neigh_if_id) in self.neighbors(id) {
+                    if !seen.insert(neigh_id) {
+                        continue;
+                    }
+                    if (id, if_id) <= (neigh_id, neigh_if_id) {
+                        links.push((id, if_id, neigh_id, neigh_if_id));
+                    } else {
+                        links.push((neigh_id, neigh_if_id, id, if_id));
+                    }
+                    queue.push_back(neigh_id);
+                }
+            }
+        }
+        links.sort();
+        links
+    }
+
+    /// Renders the topology as a Mermaid `graph LR` block. Every `LocalNet`
+    /// link appears once, labeled `a_iface:b_iface`, and every `Internet`
+    /// interface hangs off its node as an `([Internet])` stub labeled with the
+    /// interface index.
+    pub fn to_mermaid(&self) -> String {
+        let mut ids: Vec<NodeId> = self.nodes.keys().copied().collect();
+        ids.sort();
+
+        let mut res = String::from("graph LR\n");
+        for &id in &ids {
+            writeln!(res, "    n{id}[{id}]").unwrap();
+        }
+        for (a_id, a_if_id, b_id, b_if_id) in self.canonical_links() {
+            writeln!(res, "    n{a_id} ---|\"{a_if_id}:{b_if_id}\"| n{b_id}").unwrap();
+        }
+        for &id in &ids {
+            for iface in self.nodes[&id].interfaces() {
+                if iface.if_type == InterfaceType::Internet {
+                    let if_id = iface.id;
+                    writeln!(res, "    inet_{id}_{if_id}([Internet])").unwrap();
+                    writeln!(res, "    n{id} ---|\"{if_id}\"| inet_{id}_{if_id}").unwrap();
+                }
+            }
+        }
+        res
+    }
+
+    /// Renders the topology as a Graphviz `graph`. Every `LocalNet` link
+    /// appears once with the interface on each end as tail and head label,
+    /// and every `Internet` interface hangs off its node as an ellipse. Node
+    /// and interface labels are shown when set.
+    pub fn to_dot(&self) -> String {
+        let mut ids: Vec<NodeId> = self.nodes.keys().copied().collect();
+        ids.sort();
+
+        let mut res = String::from("graph topology {\n");
+        for &id in &ids {
+            let node_label = match &self.nodes[&id].label {
+                Some(label) => format!("{id}\\n{}", escape_dot(label)),
+                None => format!("{id}"),
+            };
+            writeln!(res, "    n{id} [label=\"{node_label}\"];").unwrap();
+        }
+        for (a_id, a_if_id, b_id, b_if_id) in self.canonical_links() {
+            writeln!(res, "    n{a_id} -- n{b_id} [taillabel=\"{}\", headlabel=\"{}\"];",
+                     escape_dot(&self.nodes[&a_id].ifaces[&a_if_id].to_string()),
+                     escape_dot(&self.nodes[&b_id].ifaces[&b_if_id].to_string())).unwrap();
+        }
+        for &id in &ids {
+            for iface in self.nodes[&id].interfaces() {
+                if iface.if_type == InterfaceType::Internet {
+                    let if_id = iface.id;
+                    writeln!(res, "    inet_{id}_{if_id} [label=\"Internet\", shape=ellipse];").unwrap();
+                    writeln!(res, "    n{id} -- inet_{id}_{if_id} [taillabel=\"{}\"];", escape_dot(&iface.to_string())).unwrap();
+                }
+            }
+        }
+        res.push_str("}\n");
+        res
+    }
+
+    /// Plain-text table with one row per node, sorted by id: its role
+    /// (`gateway` if it has an `Internet` interface, else `app` if it has a
+    /// `LocalApp` one, else `core`), its `LocalNet` degree with parallel
+    /// links counted separately, and its distinct neighbors.
+    pub fn to_ascii_table(&self) -> String {
+        let mut ids: Vec<NodeId> = self.nodes.keys().copied().collect();
+        ids.sort();
+
+        let mut rows: Vec<[String; 4]> = vec![["node", "role", "degree", "neighbors"].map(String::from)];
+        for &id in &ids {
+            let node = &self.nodes[&id];
+            let role = if node.is_gateway() {
+                "gateway"
+            } else if node.is_app_endpoint() {
+                "app"
+            } else {
+                "core"
+            };
+            let mut neigh_ids: Vec<NodeId> = self.local_net_neighbor_ids(id).collect();
+            let degree = neigh_ids.len();
+            neigh_ids.sort();
+            neigh_ids.dedup();
+            let neighbors: Vec<String> = neigh_ids.iter().map(NodeId::to_string).collect();
+
+            rows.push([id.to_string(), role.to_string(), degree.to_string(), neighbors.join(", ")]);
+        }
+
+        let widths: Vec<usize> = (0..3).map(|i| rows.iter().map(|row| row[i].len()).max().unwrap_or(0))
+                                       .collect();
+        let mut res = String::new();
+        for row in &rows {
+            let line = format!("{:<w0$}  {:<w1$}  {:>w2$}  {}", row[0], row[1], row[2], row[3],
+                               w0 = widths[0], w1 = widths[1], w2 = widths[2]);
+            writeln!(res, "{}", line.trim_end()).unwrap();
+        }
+        res
+    }
+}
+
+// Backslashes first, so the ones escaping quotes aren't doubled.
+fn escape_dot(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
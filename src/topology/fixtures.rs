@@ -0,0 +1,112 @@
+//! Ready-made topologies to try the search APIs on, the same ones the
+//! crate's own tests use. Every node has a `LocalApp` interface 0, and node
+//! ids start at 0xA so that they display as `A`, `B`, `C`, ….
+
+use alloc::vec::Vec;
+use alloc::vec;
+
+use super::IfaceIndex;
+use super::Interface;
+use super::NodeId;
+use super::Topology;
+use super::TopologyNode;
+
+/// `n` nodes in a row, each linked to the next through its interface 2
+/// (interface 1 on the first node) and to the previous through its
+/// interface 1:
+///
+/// ```text
+/// A(1) -- (1)B(2) -- (1)C
+/// ```
+pub fn line_topology(n: usize) -> Topology {
+    let ids: Vec<NodeId> = (0..n).map(|i| NodeId(0xA + i as u32)).collect();
+    let next_if_id = |i: usize| if i == 0 { IfaceIndex(1) } else { IfaceIndex(2) };
+
+    let mut topo = Topology::new();
+    for (i, &id) in ids.iter().enumerate() {
+        let mut node = TopologyNode::new(id);
+        node.add_iface(Interface::builder(IfaceIndex(0)).local_app().build());
+        if i > 0 {
+            node.add_iface(Interface::builder(IfaceIndex(1)).neighbor(ids[i - 1], next_if_id(i - 1)).build());
+        }
+        if i + 1 < n {
+            node.add_iface(Interface::builder(next_if_id(i)).neighbor(ids[i + 1], IfaceIndex(1)).build());
+        }
+        topo.add_node(node);
+    }
+    topo
+}
+
+/// `line_topology` with an `Internet` interface 2 on the last node:
+///
+/// ```text
+/// A(1) -- (1)B(2) -- (1)C(2) -- Internet
+/// ```
+pub fn line_with_gateway(n: usize) -> Topology {
+    let mut topo = line_topology(n);
+    if let Some(last) = n.checked_sub(1) {
+        let node = topo.get_node_mut(NodeId(0xA + last as u32));
+        node.add_iface(Interface::builder(IfaceIndex(2)).internet().build());
+    }
+    topo
+}
+
+/// Six nodes with two gateways, parallel links and several loops:
+///
+/// ```text
+/// Internet -- (1)A(2) -- (1)B(4) -- (1)C(2) -- Internet
+///                        (2)(3)    (3)(4)
+///                         |  |    /    |
+///                        (2)(3)(4)     (2)
+///                D(1) -- (1)E(5) -- (1)F
+/// ```
+pub fn sample_mesh() -> Topology {
+    let (n_a, n_b, n_c, n_d, n_e, n_f) =
+        (NodeId(0xA), NodeId(0xB), NodeId(0xC), NodeId(0xD), NodeId(0xE), NodeId(0xF));
+    let (if_0, if_1, if_2, if_3, if_4, if_5) =
+        (IfaceIndex(0), IfaceIndex(1), IfaceIndex(2), IfaceIndex(3), IfaceIndex(4), IfaceIndex(5));
+
+    let nodes = [
+        (n_a, vec![
+            Interface::builder(if_1).internet().build(),
+            Interface::builder(if_2).neighbor(n_b, if_1).build(),
+        ]),
+        (n_b, vec![
+            Interface::builder(if_1).neighbor(n_a, if_2).build(),
+            Interface::builder(if_2).neighbor(n_e, if_2).build(),
+            Interface::builder(if_3).neighbor(n_e, if_3).build(),
+            Interface::builder(if_4).neighbor(n_c, if_1).build(),
+        ]),
+        (n_c, vec![
+            Interface::builder(if_1).neighbor(n_b, if_4).build(),
+            Interface::builder(if_2).internet().build(),
+            Interface::builder(if_3).neighbor(n_e, if_4).build(),
+            Interface::builder(if_4).neighbor(n_f, if_2).build(),
+        ]),
+        (n_d, vec![
+            Interface::builder(if_1).neighbor(n_e, if_1).build(),
+        ]),
+        (n_e, vec![
+            Interface::builder(if_1).neighbor(n_d, if_1).build(),
+            Interface::builder(if_2).neighbor(n_b, if_2).build(),
+            Interface::builder(if_3).neighbor(n_b, if_3).build(),
+            Interface::builder(if_4).neighbor(n_c, if_3).build(),
+            Interface::builder(if_5).neighbor(n_f, if_1).build(),
+        ]),
+        (n_f, vec![
+            Interface::builder(if_1).neighbor(n_e, if_5).build(),
+            Interface::builder(if_2).neighbor(n_c, if_4).build(),
+        ]),
+    ];
+
+    let mut topo = Topology::new();
+    for (id, ifaces) in nodes {
+        let mut node = TopologyNode::new(id);
+        node.add_iface(Interface::builder(if_0).local_app().build());
+        for iface in ifaces {
+            node.add_iface(iface);
+        }
+        topo.add_node(node);
+    }
+    topo
+}
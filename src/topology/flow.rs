@@ -0,0 +1,209 @@
+use alloc::vec::Vec;
+use alloc::vec;
+
+use super::NodeId;
+use super::Topology;
+use super::collections::HashMap;
+use super::collections::VecDeque;
+
+// Residual network for unit-ish capacity max-flow (Edmonds-Karp).
+// Edges are stored in pairs: edge `e` and its reverse `e ^ 1`.
+pub(super) struct FlowNetwork {
+    adjacency: Vec<Vec<usize>>,
+    to: Vec<usize>,
+    cap: Vec<u32>,
+}
+
+impl FlowNetwork {
+    pub(super) fn new(size: usize) -> Self {
+        Self {
+            adjacency: vec![Vec::new(); size],
+            to: Vec::new(),
+            cap: Vec::new(),
+        }
+    }
+
+    pub(super) fn add_edge(&mut self, from: usize, to: usize, cap: u32) {
+        self.adjacency[from].push(self.to.len());
+        self.to.push(to);
+        self.cap.push(cap);
+
+        self.adjacency[to].push(self.to.len());
+        self.to.push(from);
+        self.cap.push(0);
+    }
+
+    // Pushes flow from `source` to `sink` until no augmenting path is left
+    // or `limit` units have been sent, whichever comes first.
+    pub(super) fn max_flow(&mut self, source: usize, sink: usize, limit: u32) -> u32 {
+        let mut flow = 0;
+
+        while flow < limit {
+            let parent_edge = match self.augmenting_path(source, sink) {
+                Some(parent_edge) => parent_edge,
+                None => break,
+            };
+
+            let mut bottleneck = limit - flow;
+            let mut v = sink;
+            while v != source {
+                let e = parent_edge[v].unwrap();
+                bottleneck = bottleneck.min(self.cap[e]);
+                v = self.to[e ^ 1];
+            }
+
+            let mut v = sink;
+            while v != source {
+                let e = parent_edge[v].unwrap();
+                self.cap[e] -= bottleneck;
+                self.cap[e ^ 1] += bottleneck;
+                v = self.to[e ^ 1];
+            }
+
+            flow += bottleneck;
+        }
+        flow
+    }
+
+    fn augmenting_path(&self, source: usize, sink: usize) -> Option<Vec<Option<usize>>> {
+        let mut parent_edge: Vec<Option<usize>> = vec![None; self.adjacency.len()];
+        let mut seen = vec![false; self.adjacency.len()];
+        let mut queue = VecDeque::new();
+
+        seen[source] = true;
+        queue.push_back(source);
+
+        while let Some(v) = queue.pop_front() {
+            for &e in &self.adjacency[v] {
+                let next = self.to[e];
+                if self.cap[e] > 0 && !seen[next] {
+                    seen[next] = true;
+                    parent_edge[next] = Some(e);
+                    if next == sink {
+                        return Some(parent_edge);
+                    }
+                    queue.push_back(next);
+                }
+            }
+        }
+        None
+    }
+}
+
+impl Topology {
+    /// Checks that the `LocalNet` graph stays connected after removing any
+    /// set of fewer than `k` nodes.
+    ///
+    /// By Menger's theorem this holds iff every pair of non-adjacent nodes is
+    /// joined by at least `k` node-disjoint paths, which is checked with one
+    /// max-flow per pair on the node-split graph. Each flow stops after `k`
+    /// augmentations, so the whole check is O(V² · k · (V + E)) and quickly
+    /// becomes expensive on large topologies.
+    ///
+    /// `k = 0` always holds and `k = 1` is exactly `is_connected`. For larger
+    /// `k` the topology must also have more than `k` nodes.
+    pub fn is_k_connected(&self, k: usize) -> bool {
+        match k {
+            0 => return true,
+            1 => return self.is_connected(),
+            _ => {}
+        }
+
+        if self.nodes.len() <= k {
+            return false;
+        }
+
+        let limit = u32::try_from(k).unwrap_or(u32::MAX);
+        let mut ids: Vec<NodeId> = self.nodes.keys().copied().collect();
+        ids.sort();
+
+        for (i, &a) in ids.iter().enumerate() {
+            for &b in &ids[i + 1..] {
+                if self.are_adjacent(a, b) {
+                    continue;
+                }
+                if self.node_disjoint_flow(&ids, a, b, limit) < limit {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    /// Intermediate nodes that lie on every `LocalNet` path from `a` to `b`,
+    /// sorted: removing any one of them disconnects the pair. Empty when `a`
+    /// and `b` are the same, adjacent, disconnected or unknown.
+    ///
+    /// With a single unit of node-disjoint flow, such a node is one whose
+    /// split edge belongs to some minimum cut, i.e. the residual network
+    /// has no other way from its in half to its out half.
+    pub fn separating_vertices(&self, a: NodeId, b: NodeId) -> Vec<NodeId> {
+        if a == b || !self.nodes.contains_key(&a) || !self.nodes.contains_key(&b) {
+            return Vec::new();
+        }
+
+        let mut ids: Vec<NodeId> = self.nodes.keys().copied().collect();
+        ids.sort();
+        let (mut network, index) = self.node_split_network(&ids);
+        if network.max_flow(2 * index[&a] + 1, 2 * index[&b], 2) != 1 {
+            return Vec::new();
+        }
+
+        ids.iter()
+           .enumerate()
+           .filter(|&(_, &id)| id != a && id != b)
+           .filter(|&(i, _)| network.augmenting_path(2 * i, 2 * i + 1).is_none())
+           .map(|(_, &id)| id)
+           .collect()
+    }
+
+    /// Number of `LocalNet` paths from `a` to `b` that share no link, which
+    /// by Menger's theorem is also the number of links that have to fail to
+    /// cut `a` off from `b`. Parallel links count separately. 0 when `a` and
+    /// `b` are the same, disconnected or unknown.
+    ///
+    /// One max-flow over the links with unit capacity in each direction.
+    pub fn edge_disjoint_path_count(&self, a: NodeId, b: NodeId) -> usize {
+        if a == b || !self.nodes.contains_key(&a) || !self.nodes.contains_key(&b) {
+            return 0;
+        }
+
+        let mut ids: Vec<NodeId> = self.nodes.keys().copied().collect();
+        ids.sort();
+        let index: HashMap<NodeId, usize> = ids.iter()
+                                               .enumerate()
+                                               .map(|(i, &id)| (id, i))
+                                               .collect();
+        let mut network = FlowNetwork::new(ids.len());
+
+        for (i, &id) in ids.iter().enumerate() {
+            for neigh_id in self.local_net_neighbor_ids(id) {
+                network.add_edge(i, index[&neigh_id], 1);
+            }
+        }
+        network.max_flow(index[&a], index[&b], u32::MAX) as usize
+    }
+
+    // Max-flow between `a` and `b` where every other node can carry a single
+    // unit: node `i` of `ids` is split into `2 * i` (in) and `2 * i + 1` (out).
+    fn node_disjoint_flow(&self, ids: &[NodeId], a: NodeId, b: NodeId, limit: u32) -> u32 {
+        let (mut network, index) = self.node_split_network(ids);
+        network.max_flow(2 * index[&a] + 1, 2 * index[&b], limit)
+    }
+
+    fn node_split_network(&self, ids: &[NodeId]) -> (FlowNetwork, HashMap<NodeId, usize>) {
+        let index: HashMap<NodeId, usize> = ids.iter()
+                                               .enumerate()
+                                               .map(|(i, &id)| (id, i))
+                                               .collect();
+        let mut network = FlowNetwork::new(2 * ids.len());
+
+        for (i, &id) in ids.iter().enumerate() {
+            network.add_edge(2 * i, 2 * i + 1, 1);
+            for neigh_id in self.local_net_neighbor_ids(id) {
+                network.add_edge(2 * i + 1, 2 * index[&neigh_id], 1);
+            }
+        }
+        (network, index)
+    }
+}
@@ -0,0 +1,189 @@
+use alloc::vec::Vec;
+
+use super::IfaceIndex;
+use super::NodeId;
+use super::Path;
+use super::PathError;
+use super::Topology;
+use super::collections::HashMap;
+use super::collections::HashSet;
+
+impl Topology {
+    /// Intermediate nodes shared by the shortest path from `from` to `gw_a`
+    /// and the one from `from` to `gw_b`, sorted. `from`, `gw_a` and `gw_b`
+    /// themselves are never reported. Lots of overlap means two gateways give
+    /// little real redundancy. Empty if either gateway is unreachable.
+    pub fn common_transit(&self, from: NodeId, gw_a: NodeId, gw_b: NodeId) -> Vec<NodeId> {
+        let (path_a, path_b) = match (self.shortest_path(from, gw_a), self.shortest_path(from, gw_b)) {
+            (Some(path_a), Some(path_b)) => (path_a, path_b),
+            _ => return Vec::new(),
+        };
+
+        let on_path_b: HashSet<NodeId> = path_b.nodes.iter().map(|x| x.id).collect();
+        let mut res: Vec<NodeId> = path_a.nodes
+                                         .iter()
+                                         .map(|x| x.id)
+                                         .filter(|id| on_path_b.contains(id))
+                                         .filter(|&id| id != from && id != gw_a && id != gw_b)
+                                         .collect();
+        res.sort();
+        res
+    }
+
+    /// Gateways reachable from `from` over `LocalNet` links, sorted. `from`
+    /// is included when it is a gateway itself.
+    pub fn reachable_gateways(&self, from: NodeId) -> Vec<NodeId> {
+        self.reachable_gateway_distances(from)
+            .into_iter()
+            .map(|(gw_id, _)| gw_id)
+            .collect()
+    }
+
+    /// How many distinct gateways `node` can reach, i.e. how many gateways
+    /// would all have to fail to cut it off from the Internet. 1 flags a
+    /// single-egress node; 0 means it has no Internet access at all. Paths to
+    /// different gateways may share transit nodes, so this is an upper bound
+    /// on the real redundancy.
+    pub fn gateway_resilience(&self, node: NodeId) -> usize {
+        self.reachable_gateways(node).len()
+    }
+
+    /// Fewest-hop route from `start` to `finish` that passes through at least
+    /// one gateway, for policies that force traffic through an egress point.
+    /// Built from a shortest path to a gateway and one on from it, so when
+    /// the gateway is off the direct route the path doubles back and visits
+    /// some nodes twice. Ties go to the smallest gateway id. `None` if no
+    /// gateway is reachable from both ends.
+    pub fn path_via_any_gateway(&self, start: NodeId, finish: NodeId) -> Option<Path> {
+        let mut gw_ids = self.find_internet_gateway();
+        gw_ids.sort();
+
+        let mut best: Option<Path> = None;
+        for gw_id in gw_ids {
+            let (to_gw, from_gw) = match (self.shortest_path(start, gw_id), self.shortest_path(gw_id, finish)) {
+                (Some(to_gw), Some(from_gw)) => (to_gw, from_gw),
+                _ => continue,
+            };
+            if best.as_ref().is_some_and(|path| path.nodes.len() < to_gw.nodes.len() + from_gw.nodes.len()) {
+                continue;
+            }
+
+            let mut path = to_gw;
+            let mut rest = from_gw.nodes.into_iter();
+            let gw_node = rest.next().unwrap();
+            path.nodes.back_mut().unwrap().forward_if_id = gw_node.forward_if_id;
+            path.nodes.extend(rest);
+            best = Some(path);
+        }
+        best
+    }
+
+    /// Where to add one more gateway: the non-gateway node that, made a
+    /// gateway, minimizes the largest hop distance from any node to its
+    /// nearest gateway, with that distance. Ties go to the smallest id.
+    /// Candidates that would leave some node with no reachable gateway are
+    /// skipped, so a disconnected topology only gets an answer if one new
+    /// gateway covers whatever the existing ones don't reach. `None` if
+    /// there is no such candidate.
+    ///
+    /// Brute force: one BFS per node, so O(V · (V + E)).
+    pub fn best_gateway_placement(&self) -> Option<(NodeId, usize)> {
+        let gw_ids = self.find_internet_gateway();
+        let mut nearest: HashMap<NodeId, usize> = HashMap::new();
+        for &gw_id in &gw_ids {
+            for (id, d) in self.distances_from(gw_id) {
+                let best = nearest.entry(id).or_insert(d);
+                *best = (*best).min(d);
+            }
+        }
+
+        let mut ids: Vec<NodeId> = self.nodes.keys().copied().filter(|id| !gw_ids.contains(id)).collect();
+        ids.sort();
+
+        let mut best: Option<(NodeId, usize)> = None;
+        for candidate in ids {
+            let distances = self.distances_from(candidate);
+            let worst = self.nodes
+                            .keys()
+                            .map(|id| match (nearest.get(id), distances.get(id)) {
+                                (Some(&a), Some(&b)) => Some(a.min(b)),
+                                (a, b) => a.or(b).copied(),
+                            })
+                            .try_fold(0, |worst, d| d.map(|d| worst.max(d)));
+            if let Some(worst) = worst {
+                if best.is_none_or(|(_, best_worst)| worst < best_worst) {
+                    best = Some((candidate, worst));
+                }
+            }
+        }
+        best
+    }
+
+    /// Cheapest way out of the network from `from`, as the gateway, its
+    /// `Internet` interface and the total cost: the `cost` of the links on
+    /// the way there plus the `cost` of that `Internet` interface, so a
+    /// closer gateway with a pricier uplink can lose. Interfaces that are
+    /// down aren't used. Ties go to the smallest `(gateway, interface)`.
+    pub fn nearest_gateway(&self, from: NodeId) -> Option<(NodeId, IfaceIndex, u32)> {
+        let distances = self.distances_from_weighted(from);
+
+        self.gateway_interfaces()
+            .into_iter()
+            .filter(|&(gw_id, gw_if_id)| self.nodes[&gw_id].ifaces[&gw_if_id].is_up())
+            .filter_map(|(gw_id, gw_if_id)| {
+                let cost = distances.get(&gw_id)?.saturating_add(self.link_cost(gw_id, gw_if_id));
+                Some((gw_id, gw_if_id, cost))
+            })
+            .min_by_key(|&(gw_id, gw_if_id, cost)| (cost, gw_id, gw_if_id))
+    }
+
+    /// Cheapest path from the smallest `LocalApp` interface of `from` out
+    /// through the egress picked by `nearest_gateway`, ending on that
+    /// `Internet` interface. `None` if `from` has no `LocalApp` interface.
+    pub fn default_route(&self, from: NodeId) -> Option<Path> {
+        // an unknown `from` reaches no gateway, so the lookup below is safe
+        let (gw_id, gw_if_id, _) = self.nearest_gateway(from)?;
+        let from_if_id = self.get_local_app_iface_id(from)?;
+        let mut path = self.shortest_path_weighted(from, gw_id)?;
+        path.nodes.front_mut().unwrap().reverse_if_id = from_if_id;
+        path.nodes.back_mut().unwrap().forward_if_id = gw_if_id;
+        Some(path)
+    }
+
+    /// For every node, the gateways it can reach with their hop distance,
+    /// sorted by gateway id. Nodes that reach no gateway map to an empty vec.
+    pub fn gateway_map(&self) -> HashMap<NodeId, Vec<(NodeId, usize)>> {
+        self.nodes
+            .keys()
+            .map(|&id| (id, self.reachable_gateway_distances(id)))
+            .collect()
+    }
+
+    fn reachable_gateway_distances(&self, from: NodeId) -> Vec<(NodeId, usize)> {
+        let distances = self.distances_from(from);
+        let mut res: Vec<(NodeId, usize)> = self.find_internet_gateway()
+                                                .into_iter()
+                                                .filter_map(|gw_id| distances.get(&gw_id).map(|&d| (gw_id, d)))
+                                                .collect();
+        res.sort();
+        res
+    }
+
+    /// Loop-free paths from `start` to every gateway interface it can reach,
+    /// keyed by that `Internet` interface, fewest hops first. Paths start at
+    /// the smallest `LocalApp` interface of `start` and end on the egress
+    /// interface; unreachable gateways are absent. Empty if `start` is
+    /// unknown or has no `LocalApp` interface.
+    pub fn paths_by_egress(&self, start: NodeId) -> HashMap<(NodeId, IfaceIndex), Vec<Path>> {
+        let start_if_id = match self.app_iface_id(start, PathError::StartNotFound(start)) {
+            Ok(start_if_id) => start_if_id,
+            Err(_) => return HashMap::new(),
+        };
+
+        self.gateway_interfaces()
+            .into_iter()
+            .map(|(gw_id, gw_if_id)| ((gw_id, gw_if_id), self.paths_best_first(start, start_if_id, gw_id, gw_if_id).collect::<Vec<Path>>()))
+            .filter(|(_, paths)| !paths.is_empty())
+            .collect()
+    }
+}
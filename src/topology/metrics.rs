@@ -0,0 +1,265 @@
+use alloc::vec::Vec;
+use alloc::vec;
+
+use super::IfaceIndex;
+use super::NodeId;
+use super::Topology;
+use super::collections::BTreeMap;
+use super::collections::HashMap;
+use super::collections::VecDeque;
+
+// Per node reached by a BFS: hop distance and the link from its parent,
+// `(parent, parent_iface, iface)`, or `None` for the root.
+type BfsTree = HashMap<NodeId, (usize, Option<(NodeId, IfaceIndex, IfaceIndex)>)>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ComponentInfo {
+    /// Members, sorted.
+    pub nodes: Vec<NodeId>,
+    pub diameter: usize,
+    /// Whether any member has an `Internet` interface.
+    pub has_gateway: bool,
+}
+
+impl ComponentInfo {
+    pub fn size(&self) -> usize {
+        self.nodes.len()
+    }
+}
+
+impl Topology {
+    /// Hop distances between every pair of mutually reachable nodes, one BFS
+    /// per node.
+    pub fn all_pairs_shortest_hops(&self) -> HashMap<NodeId, HashMap<NodeId, usize>> {
+        self.nodes
+            .keys()
+            .map(|&id| (id, self.distances_from(id)))
+            .collect()
+    }
+
+    /// Largest hop distance from `id` to any node of its own component.
+    pub fn eccentricity(&self, id: NodeId) -> Option<usize> {
+        if !self.nodes.contains_key(&id) {
+            return None;
+        }
+        self.distances_from(id).into_values().max()
+    }
+
+    /// How many nodes lie at each hop distance from `from`, `from` itself
+    /// counting at distance 0. Unreachable nodes are left out, and an
+    /// unknown `from` gives an empty map.
+    pub fn hop_histogram(&self, from: NodeId) -> BTreeMap<usize, usize> {
+        let mut histogram: BTreeMap<usize, usize> = BTreeMap::new();
+        for hops in self.distances_from(from).into_values() {
+            *histogram.entry(hops).or_insert(0) += 1;
+        }
+        histogram
+    }
+
+    /// Largest eccentricity of any node, i.e. the largest diameter among the
+    /// connected components. `None` for an empty topology.
+    pub fn diameter(&self) -> Option<usize> {
+        self.nodes.keys().filter_map(|&id| self.eccentricity(id)).max()
+    }
+
+    /// The pair of nodes not linked yet whose new `LocalNet` link would give
+    /// the smallest `diameter`, with that diameter. Ties go to the smallest
+    /// pair. `None` if every pair is linked already.
+    ///
+    /// Every candidate pair is scored against the current all-pairs hop
+    /// table rather than by rerunning BFS, but that is still O(V²) per pair
+    /// and O(V⁴) overall: fine for a few hundred nodes, slow beyond.
+    pub fn best_link_to_add(&self) -> Option<(NodeId, NodeId, usize)> {
+        let mut ids: Vec<NodeId> = self.nodes.keys().copied().collect();
+        ids.sort();
+        let all_pairs = self.all_pairs_shortest_hops();
+        let hops = |u: NodeId, v: NodeId| all_pairs[&u].get(&v).copied();
+
+        let mut best: Option<(NodeId, NodeId, usize)> = None;
+        for (i, &a) in ids.iter().enumerate() {
+            for &b in &ids[i + 1..] {
+                if self.are_adjacent(a, b) {
+                    continue;
+                }
+
+                let mut diameter = 0;
+                for (j, &u) in ids.iter().enumerate() {
+                    for &v in &ids[j + 1..] {
+                        let via_a_b = hops(u, a).zip(hops(b, v)).map(|(x, y)| x + 1 + y);
+                        let via_b_a = hops(u, b).zip(hops(a, v)).map(|(x, y)| x + 1 + y);
+                        if let Some(d) = [hops(u, v), via_a_b, via_b_a].into_iter().flatten().min() {
+                            diameter = diameter.max(d);
+                        }
+                    }
+                }
+
+                if best.is_none_or(|(_, _, x)| diameter < x) {
+                    best = Some((a, b, diameter));
+                }
+            }
+        }
+        best
+    }
+
+    /// Where to host a service shared by the apps: the node that reaches the
+    /// most app endpoints and, among those, has the smallest sum of hop
+    /// distances to them (a 1-median), with that sum. An app endpoint is 0
+    /// hops from itself. Ties go to the smallest id; `None` if there are no
+    /// app endpoints.
+    ///
+    /// Brute force: one BFS per node, so O(V · (V + E)).
+    pub fn best_service_node(&self) -> Option<(NodeId, usize)> {
+        let apps = self.app_endpoints();
+        if apps.is_empty() {
+            return None;
+        }
+
+        let mut ids: Vec<NodeId> = self.nodes.keys().copied().collect();
+        ids.sort();
+        ids.into_iter()
+            .map(|id| {
+                let distances = self.distances_from(id);
+                let reached: Vec<usize> = apps.iter().filter_map(|app| distances.get(app).copied()).collect();
+                (id, reached.len(), reached.iter().sum::<usize>())
+            })
+            .min_by_key(|&(id, reached, total)| (core::cmp::Reverse(reached), total, id))
+            .map(|(id, _, total)| (id, total))
+    }
+
+    /// One entry per connected component, the largest first and ties ordered
+    /// by smallest `NodeId`.
+    pub fn component_report(&self) -> Vec<ComponentInfo> {
+        let mut res: Vec<ComponentInfo> = self
+            .connected_components()
+            .into_iter()
+            .map(|nodes| ComponentInfo {
+                diameter: nodes.iter().filter_map(|&id| self.eccentricity(id)).max().unwrap_or(0),
+                has_gateway: nodes.iter().any(|id| self.get_internet_iface_id(*id).is_some()),
+                nodes,
+            })
+            .collect();
+        // stable, so equal sizes keep the order of `connected_components`
+        res.sort_by_key(|x| core::cmp::Reverse(x.nodes.len()));
+        res
+    }
+
+    /// `connected_components` of a copy with the `failed` nodes and every
+    /// link to them removed, e.g. to see whether losing a whole rack splits
+    /// the network. Ids that aren't in the topology are ignored, and `self`
+    /// is left untouched.
+    pub fn connectivity_after_failures(&self, failed: &[NodeId]) -> Vec<Vec<NodeId>> {
+        let mut topo = self.clone();
+        for id in failed {
+            topo.nodes.remove(id);
+        }
+        for node in topo.nodes.values_mut() {
+            for iface in node.ifaces.values_mut() {
+                iface.neighbors.retain(|(neigh_id, _)| !failed.contains(neigh_id));
+            }
+        }
+        topo.connected_components()
+    }
+
+    /// Nodes of minimum eccentricity in each connected component, sorted.
+    pub fn center(&self) -> Vec<NodeId> {
+        self.extreme_eccentricity_nodes(|ecc, best| ecc < best)
+    }
+
+    /// Nodes of maximum eccentricity in each connected component, sorted.
+    pub fn periphery(&self) -> Vec<NodeId> {
+        self.extreme_eccentricity_nodes(|ecc, best| ecc > best)
+    }
+
+    /// Length of the shortest `LocalNet` cycle in any component, `None` if
+    /// there is none. Two parallel links between the same nodes make a cycle
+    /// of length 2.
+    pub fn girth(&self) -> Option<usize> {
+        self.shortest_cycle().map(|cycle| cycle.len())
+    }
+
+    /// Nodes of a shortest `LocalNet` cycle in order, starting from the
+    /// smallest id and without repeating it at the end. Every link that
+    /// closes a loop in the BFS tree of some node is a candidate; the
+    /// shortest candidate over all roots is a simple cycle. O(V·E).
+    pub fn shortest_cycle(&self) -> Option<Vec<NodeId>> {
+        let mut roots: Vec<NodeId> = self.nodes.keys().copied().collect();
+        roots.sort();
+
+        let mut best: Option<Vec<NodeId>> = None;
+        for root in roots {
+            let mut tree: BfsTree = HashMap::from([(root, (0, None))]);
+            let mut queue: VecDeque<NodeId> = VecDeque::from([root]);
+
+            while let Some(id) = queue.pop_front() {
+                let (dist, reached_by) = tree[&id];
+                for (if_id, neigh_id, neigh_if_id) in self.neighbors(id) {
+                    if reached_by == Some((neigh_id, neigh_if_id, if_id)) {
+                        continue;
+                    }
+                    match tree.get(&neigh_id) {
+                        None => {
+                            tree.insert(neigh_id, (dist + 1, Some((id, if_id, neigh_if_id))));
+                            queue.push_back(neigh_id);
+                        }
+                        Some(&(neigh_dist, _)) => {
+                            if best.as_ref().is_none_or(|x| dist + neigh_dist + 1 < x.len()) {
+                                best = Some(Self::close_cycle(&tree, id, neigh_id));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        best.map(|mut cycle| {
+            let start = (0..cycle.len()).min_by_key(|&i| cycle[i]).unwrap();
+            cycle.rotate_left(start);
+            cycle
+        })
+    }
+
+    // The cycle made of the BFS tree branches down to `a` and `b` plus the
+    // link between them.
+    fn close_cycle(tree: &BfsTree, a: NodeId, b: NodeId) -> Vec<NodeId> {
+        let branch = |mut id: NodeId| {
+            let mut ids: Vec<NodeId> = vec![id];
+            while let Some((parent_id, _, _)) = tree[&id].1 {
+                ids.push(parent_id);
+                id = parent_id;
+            }
+            ids
+        };
+
+        let mut cycle: Vec<NodeId> = branch(a);
+        cycle.reverse();
+        let mut b_branch = branch(b);
+        b_branch.pop(); // the root, already first in `cycle`
+        cycle.extend(b_branch);
+        cycle
+    }
+
+    fn extreme_eccentricity_nodes<F>(&self, better: F) -> Vec<NodeId>
+    where
+        F: Fn(usize, usize) -> bool,
+    {
+        let all_pairs = self.all_pairs_shortest_hops();
+        let mut res: Vec<NodeId> = Vec::new();
+
+        for component in self.connected_components() {
+            let eccentricities: Vec<(NodeId, usize)> = component
+                .iter()
+                .map(|id| (*id, all_pairs[id].values().copied().max().unwrap_or(0)))
+                .collect();
+
+            let mut best = eccentricities[0].1;
+            for &(_, ecc) in &eccentricities {
+                if better(ecc, best) {
+                    best = ecc;
+                }
+            }
+            res.extend(eccentricities.iter().filter(|x| x.1 == best).map(|x| x.0));
+        }
+        res.sort();
+        res
+    }
+}
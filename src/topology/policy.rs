@@ -0,0 +1,54 @@
+use alloc::vec::Vec;
+
+use super::IfaceIndex;
+use super::InterfaceType;
+use super::NodeId;
+use super::Path;
+use super::PathError;
+use super::Topology;
+use super::collections::HashSet;
+
+/// Interface type transitions a path may not make at a node, from the type
+/// of the interface it entered through to the type of the one it leaves
+/// through. The default policy allows everything.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TransitPolicy {
+    forbidden: HashSet<(InterfaceType, InterfaceType)>,
+}
+
+impl TransitPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Policy that forbids hairpinning from one `Internet` interface straight
+    /// into another.
+    pub fn no_internet_transit() -> Self {
+        Self::new().forbid(InterfaceType::Internet, InterfaceType::Internet)
+    }
+
+    pub fn forbid(mut self, from: InterfaceType, to: InterfaceType) -> Self {
+        self.forbidden.insert((from, to));
+        self
+    }
+
+    pub fn allows(&self, from: InterfaceType, to: InterfaceType) -> bool {
+        !self.forbidden.contains(&(from, to))
+    }
+}
+
+impl Topology {
+    /// Like `find_paths`, but never leaves a node through an interface whose
+    /// type `policy` forbids after the type of the interface the node was
+    /// entered through. On `start_id` that is `start_if_id`; `finish_if_id`
+    /// is an endpoint and isn't checked.
+    pub fn find_paths_with_policy(&self,
+                                  start_id: NodeId,
+                                  start_if_id: IfaceIndex,
+                                  finish_id: NodeId,
+                                  finish_if_id: IfaceIndex,
+                                  policy: TransitPolicy) -> Result<Vec<Path>, PathError> {
+        self.find_paths_filtered(start_id, start_if_id, finish_id, finish_if_id,
+                                 |step| step.entry.is_none_or(|entry| policy.allows(entry.if_type, step.exit.if_type)))
+    }
+}
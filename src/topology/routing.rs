@@ -0,0 +1,53 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+use super::IfaceIndex;
+use super::NodeId;
+use super::Topology;
+
+/// One forwarding entry: traffic for `destination` leaves through `iface`
+/// towards `next_hop`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Route {
+    pub destination: NodeId,
+    pub iface: IfaceIndex,
+    pub next_hop: NodeId,
+}
+
+impl Topology {
+    /// Forwarding entries of `id` for every other node it can reach, along
+    /// the fewest-hop routes and sorted by destination. Empty for an unknown
+    /// node.
+    pub fn routing_table(&self, id: NodeId) -> Vec<Route> {
+        let (_, prev) = self.dijkstra(id, None, |hops: usize, _, _, _, _| Some(hops + 1));
+
+        let mut routes: Vec<Route> = prev.keys()
+                                         .map(|&destination| {
+                                             let mut hop = destination;
+                                             while prev[&hop].0 != id {
+                                                 hop = prev[&hop].0;
+                                             }
+                                             Route { destination, iface: prev[&hop].1, next_hop: hop }
+                                         })
+                                         .collect();
+        routes.sort_by_key(|route| route.destination);
+        routes
+    }
+
+    /// The routing table of every node as text, one
+    /// `A: dest=C via iface=2 nexthop=B` line per entry, sorted by node and
+    /// then destination.
+    pub fn dump_fibs(&self) -> String {
+        let mut ids: Vec<NodeId> = self.nodes.keys().copied().collect();
+        ids.sort();
+
+        let mut res = String::new();
+        for id in ids {
+            for route in self.routing_table(id) {
+                writeln!(res, "{id}: dest={} via iface={} nexthop={}", route.destination, route.iface, route.next_hop).unwrap();
+            }
+        }
+        res
+    }
+}
@@ -0,0 +1,67 @@
+use alloc::vec::Vec;
+
+use super::NodeId;
+use super::Path;
+use super::PathError;
+use super::Topology;
+
+// How many of the shortest paths `sample_path` chooses from.
+const SAMPLE_PATH_CANDIDATES: usize = 64;
+
+// SplitMix64, plenty for reproducible simulations and no dependency.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    // uniform in [0, 1)
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+impl Topology {
+    /// Picks one loop-free path from `start_id` to `finish_id` at random,
+    /// with probability inversely proportional to its `total_cost`; a cost of
+    /// 0 weighs as much as a cost of 1. The same `seed` always picks the same
+    /// path.
+    ///
+    /// Only the 64 paths with the fewest hops are candidates, which keeps the
+    /// enumeration bounded on dense graphs. Like `find_paths_between`, the
+    /// path runs between the smallest `LocalApp` interfaces of both ends, so
+    /// the sampled path is one traffic between the two apps could take
+    /// rather than one between arbitrary interfaces.
+    ///
+    /// `None` if either end has no `LocalApp` interface, as there is then no
+    /// app traffic to model, and when both ends are the same node: traffic
+    /// that never leaves the node has no route to choose.
+    pub fn sample_path(&self, start_id: NodeId, finish_id: NodeId, seed: u64) -> Option<Path> {
+        if start_id == finish_id {
+            return None;
+        }
+        let start_if_id = self.app_iface_id(start_id, PathError::StartNotFound(start_id)).ok()?;
+        let finish_if_id = self.app_iface_id(finish_id, PathError::FinishNotFound(finish_id)).ok()?;
+
+        let candidates: Vec<Path> = self.paths_best_first(start_id, start_if_id, finish_id, finish_if_id)
+                                        .take(SAMPLE_PATH_CANDIDATES)
+                                        .collect();
+        let weights: Vec<f64> = candidates.iter()
+                                          .map(|path| 1.0 / f64::from(path.total_cost(self).max(1)))
+                                          .collect();
+
+        let mut target = SplitMix64(seed).next_f64() * weights.iter().sum::<f64>();
+        for (path, weight) in candidates.iter().zip(&weights) {
+            if target < *weight {
+                return Some(path.clone());
+            }
+            target -= weight;
+        }
+        candidates.last().cloned()
+    }
+}
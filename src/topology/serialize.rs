@@ -0,0 +1,72 @@
+use alloc::vec::Vec;
+
+use serde::Serialize;
+use serde::Serializer;
+
+use super::Interface;
+use super::InterfaceType;
+use super::Topology;
+
+// What `Topology` serializes as. Everything is sorted, nodes by id,
+// interfaces by index and neighbors by `(node, interface)`, so equal
+// topologies give identical output whatever order their maps iterate in.
+// Labels are left out: `==` ignores them, so they would let equal
+// topologies serialize differently.
+#[derive(Serialize)]
+struct NodeRecord {
+    id: u32,
+    interfaces: Vec<InterfaceRecord>,
+}
+
+#[derive(Serialize)]
+struct InterfaceRecord {
+    index: u8,
+    #[serde(rename = "type")]
+    if_type: &'static str,
+    neighbors: Vec<(u32, u8)>,
+    cost: u32,
+    explicit_cost: bool,
+    bandwidth: u32,
+    admin_up: bool,
+    oper_up: bool,
+}
+
+#[derive(Serialize)]
+struct TopologyRecord {
+    nodes: Vec<NodeRecord>,
+}
+
+impl Serialize for Topology {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut nodes: Vec<NodeRecord> = self.nodes
+                                             .values()
+                                             .map(|node| NodeRecord {
+                                                 id: node.id.0,
+                                                 interfaces: node.interfaces().map(interface_record).collect(),
+                                             })
+                                             .collect();
+        nodes.sort_by_key(|x| x.id);
+        TopologyRecord { nodes }.serialize(serializer)
+    }
+}
+
+fn interface_record(iface: &Interface) -> InterfaceRecord {
+    let mut neighbors: Vec<(u32, u8)> = iface.neighbors.iter().map(|(id, if_id)| (id.0, if_id.0)).collect();
+    neighbors.sort();
+
+    InterfaceRecord {
+        index: iface.id.0,
+        if_type: match iface.if_type {
+            InterfaceType::LocalApp => "local_app",
+            InterfaceType::LocalNet => "local_net",
+            InterfaceType::Internet => "internet",
+            InterfaceType::SharedMedium => "shared_medium",
+        },
+        neighbors,
+        cost: iface.cost,
+        explicit_cost: iface.explicit_cost,
+        bandwidth: iface.bandwidth,
+        admin_up: iface.admin_up,
+        oper_up: iface.oper_up,
+    }
+}
@@ -0,0 +1,105 @@
+use alloc::vec::Vec;
+use core::fmt::Display;
+
+use super::IfaceIndex;
+use super::Interface;
+use super::InterfaceType;
+use super::NodeId;
+use super::Topology;
+use super::collections::HashMap;
+
+/// Summary counts from `Topology::stats`. A link is administratively down if
+/// either end is; it is operationally down if it isn't administratively down
+/// but either end has failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TopologyStats {
+    pub nodes: usize,
+    pub links: usize,
+    pub admin_down_links: usize,
+    pub oper_down_links: usize,
+}
+
+// Suffix for a noun counted `n` times.
+fn plural(n: usize) -> &'static str {
+    if n == 1 { "" } else { "s" }
+}
+
+impl Display for TopologyStats {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_fmt(format_args!("{} node{}, {} link{}, {} link{} administratively down, {} link{} operationally down",
+                                 self.nodes, plural(self.nodes),
+                                 self.links, plural(self.links),
+                                 self.admin_down_links, plural(self.admin_down_links),
+                                 self.oper_down_links, plural(self.oper_down_links)))
+    }
+}
+
+impl Topology {
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Number of `LocalNet` links, each counted once however many sides list
+    /// it. Parallel links count separately.
+    pub fn edge_count(&self) -> usize {
+        self.canonical_links().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Node and link counts, including how many links are down and why.
+    pub fn stats(&self) -> TopologyStats {
+        let iface = |id: NodeId, if_id: IfaceIndex| self.nodes.get(&id).and_then(|node| node.ifaces.get(&if_id));
+        let ends_all = |a: Option<&Interface>, b: Option<&Interface>, up: fn(&Interface) -> bool| {
+            a.is_none_or(up) && b.is_none_or(up)
+        };
+
+        let links = self.canonical_links();
+        let mut admin_down_links = 0;
+        let mut oper_down_links = 0;
+        for &(a_id, a_if_id, b_id, b_if_id) in &links {
+            let (a, b) = (iface(a_id, a_if_id), iface(b_id, b_if_id));
+            if !ends_all(a, b, Interface::is_admin_up) {
+                admin_down_links += 1;
+            } else if !ends_all(a, b, Interface::is_oper_up) {
+                oper_down_links += 1;
+            }
+        }
+
+        TopologyStats {
+            nodes: self.nodes.len(),
+            links: links.len(),
+            admin_down_links,
+            oper_down_links,
+        }
+    }
+
+    /// Every administratively down interface, sorted. Their links stay in the
+    /// topology but are skipped by every search.
+    pub fn down_links(&self) -> Vec<(NodeId, IfaceIndex)> {
+        let mut down: Vec<(NodeId, IfaceIndex)> =
+            self.nodes
+                .values()
+                .flat_map(|node| node.ifaces.values().map(move |iface| (node.id, iface)))
+                .filter(|(_, iface)| !iface.is_admin_up())
+                .map(|(id, iface)| (id, iface.id))
+                .collect();
+        down.sort();
+        down
+    }
+
+    /// How many interfaces of each type exist across all nodes. Types with
+    /// no interface at all are absent.
+    pub fn interface_type_counts(&self) -> HashMap<InterfaceType, usize> {
+        let mut counts: HashMap<InterfaceType, usize> = HashMap::new();
+
+        for node in self.nodes.values() {
+            for iface in node.ifaces.values() {
+                *counts.entry(iface.if_type).or_insert(0) += 1;
+            }
+        }
+        counts
+    }
+}
@@ -0,0 +1,95 @@
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+
+use super::NodeId;
+use super::Topology;
+use super::collections::HashMap;
+
+/// Union-find over the `LocalNet` graph for answering "are these two nodes
+/// connected" while links are being added, in near-constant amortized time
+/// per operation.
+///
+/// Call `union` for every link added to the topology after the tracker was
+/// built. Removing a link or node can split a component, which union-find
+/// can't express: build a fresh tracker with `Topology::connectivity_tracker`
+/// after any removal.
+#[derive(Debug, Clone, Default)]
+pub struct ConnectivityTracker {
+    index: HashMap<NodeId, usize>,
+    parent: Vec<usize>,
+    rank: Vec<u8>,
+}
+
+impl ConnectivityTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts tracking `id` as a component of its own, if it isn't tracked yet.
+    pub fn add_node(&mut self, id: NodeId) {
+        self.slot(id);
+    }
+
+    /// Records that `a` and `b` are now linked, tracking either one if it
+    /// wasn't yet. Returns `true` if this joined two separate components.
+    pub fn union(&mut self, a: NodeId, b: NodeId) -> bool {
+        let (a_slot, b_slot) = (self.slot(a), self.slot(b));
+        let (a_root, b_root) = (self.find(a_slot), self.find(b_slot));
+        if a_root == b_root {
+            return false;
+        }
+
+        match self.rank[a_root].cmp(&self.rank[b_root]) {
+            Ordering::Less => self.parent[a_root] = b_root,
+            Ordering::Greater => self.parent[b_root] = a_root,
+            Ordering::Equal => {
+                self.parent[b_root] = a_root;
+                self.rank[a_root] += 1;
+            }
+        }
+        true
+    }
+
+    /// Whether `a` and `b` are in the same component. Untracked nodes are
+    /// connected to nothing.
+    pub fn connected(&mut self, a: NodeId, b: NodeId) -> bool {
+        match (self.index.get(&a), self.index.get(&b)) {
+            (Some(&a_slot), Some(&b_slot)) => self.find(a_slot) == self.find(b_slot),
+            _ => false,
+        }
+    }
+
+    fn slot(&mut self, id: NodeId) -> usize {
+        let next = self.parent.len();
+        let slot = *self.index.entry(id).or_insert(next);
+        if slot == next {
+            self.parent.push(slot);
+            self.rank.push(0);
+        }
+        slot
+    }
+
+    // Root of `slot`'s tree, halving the path on the way up.
+    fn find(&mut self, mut slot: usize) -> usize {
+        while self.parent[slot] != slot {
+            self.parent[slot] = self.parent[self.parent[slot]];
+            slot = self.parent[slot];
+        }
+        slot
+    }
+}
+
+impl Topology {
+    /// Tracker seeded with every node and `LocalNet` link of the topology.
+    pub fn connectivity_tracker(&self) -> ConnectivityTracker {
+        let mut tracker = ConnectivityTracker::new();
+
+        for &id in self.nodes.keys() {
+            tracker.add_node(id);
+            for neigh_id in self.local_net_neighbor_ids(id) {
+                tracker.union(id, neigh_id);
+            }
+        }
+        tracker
+    }
+}
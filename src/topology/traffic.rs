@@ -0,0 +1,62 @@
+use alloc::vec::Vec;
+
+use super::IfaceIndex;
+use super::NodeId;
+use super::Topology;
+use super::collections::HashMap;
+
+impl Topology {
+    /// Routes every `(source, destination, amount)` demand along the
+    /// `shortest_path` between its endpoints and sums the offered load on
+    /// each interface traffic leaves through, to compare against the
+    /// interface `bandwidth`. Demands with an unreachable or unknown endpoint
+    /// are skipped; `unroutable_demands` lists them.
+    pub fn apply_demands(&self, demands: &[(NodeId, NodeId, u32)]) -> HashMap<(NodeId, IfaceIndex), u32> {
+        let mut load: HashMap<(NodeId, IfaceIndex), u32> = HashMap::new();
+
+        for &(source, destination, amount) in demands {
+            let path = match self.shortest_path(source, destination) {
+                Some(path) => path,
+                None => continue,
+            };
+            for hop in path.nodes.iter().take(path.nodes.len() - 1) {
+                let iface_load = load.entry((hop.id, hop.forward_if_id)).or_default();
+                *iface_load = iface_load.saturating_add(amount);
+            }
+        }
+        load
+    }
+
+    /// Nodes ranked by the demand routed through them, highest first and
+    /// ties by id. Demands follow `shortest_path` as in `apply_demands`, and
+    /// only transit counts: a demand's own endpoints don't carry it. Nodes
+    /// that carry nothing are left out.
+    pub fn flow_choke_points(&self, demands: &[(NodeId, NodeId, u32)]) -> Vec<(NodeId, u32)> {
+        let mut load: HashMap<NodeId, u32> = HashMap::new();
+
+        for &(source, destination, amount) in demands {
+            let path = match self.shortest_path(source, destination) {
+                Some(path) => path,
+                None => continue,
+            };
+            for hop in path.nodes.iter().skip(1).take(path.nodes.len().saturating_sub(2)) {
+                let node_load = load.entry(hop.id).or_default();
+                *node_load = node_load.saturating_add(amount);
+            }
+        }
+
+        let mut res: Vec<(NodeId, u32)> = load.into_iter()
+                                              .filter(|&(_, amount)| amount > 0)
+                                              .collect();
+        res.sort_by_key(|&(id, amount)| (core::cmp::Reverse(amount), id));
+        res
+    }
+
+    /// The demands `apply_demands` has to skip, in their original order.
+    pub fn unroutable_demands(&self, demands: &[(NodeId, NodeId, u32)]) -> Vec<(NodeId, NodeId, u32)> {
+        demands.iter()
+               .filter(|(source, destination, _)| self.shortest_path(*source, *destination).is_none())
+               .copied()
+               .collect()
+    }
+}
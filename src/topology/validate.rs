@@ -0,0 +1,121 @@
+use alloc::vec::Vec;
+
+use super::IfaceIndex;
+use super::NodeId;
+use super::Topology;
+use super::collections::BTreeMap;
+use super::collections::HashSet;
+
+impl Topology {
+    /// Every `(node, iface, neighbor, neighbor_iface)` neighbor entry that
+    /// repeats an earlier entry of the same interface, once per extra copy.
+    /// Sorted; nothing is modified.
+    pub fn duplicate_neighbors(&self) -> Vec<(NodeId, IfaceIndex, NodeId, IfaceIndex)> {
+        let mut res: Vec<(NodeId, IfaceIndex, NodeId, IfaceIndex)> = Vec::new();
+
+        for (&n_id, node) in self.nodes.iter() {
+            for (&if_id, iface) in node.ifaces.iter() {
+                let mut seen: HashSet<(NodeId, IfaceIndex)> = HashSet::new();
+                for &(neigh_id, neigh_if_id) in &iface.neighbors {
+                    if !seen.insert((neigh_id, neigh_if_id)) {
+                        res.push((n_id, if_id, neigh_id, neigh_if_id));
+                    }
+                }
+            }
+        }
+        res.sort();
+        res
+    }
+
+    /// Drops repeated entries from every neighbor list, keeping the first
+    /// occurrence in place, and returns how many were removed.
+    pub fn deduplicate_neighbors(&mut self) -> usize {
+        let mut removed = 0;
+
+        for node in self.nodes.values_mut() {
+            for iface in node.ifaces.values_mut() {
+                let mut seen: HashSet<(NodeId, IfaceIndex)> = HashSet::new();
+                let before = iface.neighbors.len();
+                iface.neighbors.retain(|&neighbor| seen.insert(neighbor));
+                removed += before - iface.neighbors.len();
+            }
+        }
+        removed
+    }
+
+    /// Interfaces listing their own node as a neighbor, sorted.
+    pub fn self_loops(&self) -> Vec<(NodeId, IfaceIndex)> {
+        let mut res: Vec<(NodeId, IfaceIndex)> = Vec::new();
+
+        for (&n_id, node) in self.nodes.iter() {
+            for (&if_id, iface) in node.ifaces.iter() {
+                if iface.neighbors.iter().any(|(neigh_id, _)| *neigh_id == n_id) {
+                    res.push((n_id, if_id));
+                }
+            }
+        }
+        res.sort();
+        res
+    }
+
+    /// Neighbor entries naming a node that isn't in the topology, as
+    /// `(node, iface, missing_neighbor)`, sorted.
+    pub fn dangling_neighbors(&self) -> Vec<(NodeId, IfaceIndex, NodeId)> {
+        let mut res: Vec<(NodeId, IfaceIndex, NodeId)> = Vec::new();
+
+        for (&n_id, node) in self.nodes.iter() {
+            for (&if_id, iface) in node.ifaces.iter() {
+                for &(neigh_id, _) in &iface.neighbors {
+                    if !self.nodes.contains_key(&neigh_id) {
+                        res.push((n_id, if_id, neigh_id));
+                    }
+                }
+            }
+        }
+        res.sort();
+        res
+    }
+
+    /// Neighbor entries `(node, iface, neighbor, neighbor_iface)` whose
+    /// neighbor interface is missing or doesn't list `(node, iface)` back,
+    /// sorted. Dangling neighbors are left to `dangling_neighbors`.
+    pub fn asymmetric_links(&self) -> Vec<(NodeId, IfaceIndex, NodeId, IfaceIndex)> {
+        let mut res: Vec<(NodeId, IfaceIndex, NodeId, IfaceIndex)> = Vec::new();
+
+        for (&n_id, node) in self.nodes.iter() {
+            for (&if_id, iface) in node.ifaces.iter() {
+                for &(neigh_id, neigh_if_id) in &iface.neighbors {
+                    let neigh_node = match self.nodes.get(&neigh_id) {
+                        Some(neigh_node) => neigh_node,
+                        None => continue,
+                    };
+                    let links_back = neigh_node.ifaces
+                                               .get(&neigh_if_id)
+                                               .is_some_and(|x| x.neighbors.contains(&(n_id, if_id)));
+                    if !links_back {
+                        res.push((n_id, if_id, neigh_id, neigh_if_id));
+                    }
+                }
+            }
+        }
+        res.sort();
+        res
+    }
+
+    /// Node pairs joined by more than one `LocalNet` link, with the
+    /// `(a_iface, b_iface)` pair of every such link. Pairs are reported with
+    /// `a < b`, sorted; nothing is modified.
+    #[allow(clippy::type_complexity)]
+    pub fn parallel_links(&self) -> Vec<(NodeId, NodeId, Vec<(IfaceIndex, IfaceIndex)>)> {
+        let mut pairs: BTreeMap<(NodeId, NodeId), Vec<(IfaceIndex, IfaceIndex)>> = BTreeMap::new();
+
+        for (a_id, a_if_id, b_id, b_if_id) in self.canonical_links() {
+            pairs.entry((a_id, b_id)).or_default().push((a_if_id, b_if_id));
+        }
+
+        pairs.into_iter()
+             .filter(|(_, links)| links.len() > 1)
+             .map(|((a_id, b_id), links)| (a_id, b_id, links))
+             .collect()
+    }
+}
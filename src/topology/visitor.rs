@@ -0,0 +1,182 @@
+use alloc::vec::Vec;
+use core::ops::ControlFlow;
+
+use super::IfaceIndex;
+use super::Interface;
+use super::NodeId;
+use super::Path;
+use super::PathError;
+use super::PathResult;
+use super::Topology;
+use super::non_empty;
+use super::collections::HashSet;
+
+/// Hooks into the depth-first search behind `find_paths`, for analyses that
+/// need more than the list of paths. Only `on_complete` is required.
+pub trait PathVisitor {
+    /// `node` was appended to the path being explored.
+    fn on_enter(&mut self, _node: NodeId) {}
+
+    /// `path` reached the finish after `hops` steps adding up to `cost`, as
+    /// `Path::total_cost` would give. Return `ControlFlow::Break` to end the
+    /// whole search early.
+    fn on_complete(&mut self, path: &Path, hops: usize, cost: u32) -> ControlFlow<()>;
+
+    /// `node` was taken off the path again after its branch was explored.
+    /// The start node is never taken off.
+    fn on_leave(&mut self, _node: NodeId) {}
+
+    /// The search didn't step to `node` because it is already on the path.
+    fn on_prune(&mut self, _node: NodeId) {}
+}
+
+/// A step the path search is about to take, from `node` to `next`, as
+/// shown to the filter of `Topology::find_paths_filtered`.
+#[derive(Debug, Clone, Copy)]
+pub struct StepContext<'a> {
+    pub node: NodeId,
+    /// Interface the path entered `node` through, if `node` has it. On the
+    /// start node this is the start interface.
+    pub entry: Option<&'a Interface>,
+    /// Interface the step leaves `node` through.
+    pub exit: &'a Interface,
+    pub next: NodeId,
+    /// Interface the step enters `next` through.
+    pub next_if_id: IfaceIndex,
+    /// Summed `cost` of the steps taken so far, saturating at `u32::MAX`.
+    pub cost: u32,
+    /// Steps taken so far.
+    pub hops: usize,
+}
+
+/// How much work a `find_paths_with_stats` search did.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SearchStats {
+    /// Most hops any explored path reached, dead ends included.
+    pub max_depth: usize,
+    /// Nodes appended to the path being explored, the start included.
+    pub nodes_expanded: usize,
+    pub paths_found: usize,
+}
+
+// Visitor behind `find_path`, collecting every path.
+pub(super) struct PathCollector<'a>(pub(super) &'a mut Vec<Path>);
+
+impl PathVisitor for PathCollector<'_> {
+    fn on_complete(&mut self, path: &Path, _hops: usize, _cost: u32) -> ControlFlow<()> {
+        self.0.push(path.clone());
+        ControlFlow::Continue(())
+    }
+}
+
+// Visitor behind `find_paths_with_progress`, collecting every path and
+// reporting the count every `every` paths.
+struct ProgressCollector<'a, F: FnMut(usize)> {
+    paths: &'a mut Vec<Path>,
+    every: usize,
+    progress: F,
+}
+
+impl<F: FnMut(usize)> PathVisitor for ProgressCollector<'_, F> {
+    fn on_complete(&mut self, path: &Path, _hops: usize, _cost: u32) -> ControlFlow<()> {
+        self.paths.push(path.clone());
+        if self.paths.len().is_multiple_of(self.every) {
+            (self.progress)(self.paths.len());
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+// Visitor behind `find_paths_with_stats`.
+struct StatsCollector<'a> {
+    paths: &'a mut Vec<Path>,
+    stats: SearchStats,
+    depth: usize,
+}
+
+impl PathVisitor for StatsCollector<'_> {
+    fn on_enter(&mut self, _node: NodeId) {
+        self.stats.max_depth = self.stats.max_depth.max(self.depth);
+        self.stats.nodes_expanded += 1;
+        self.depth += 1;
+    }
+
+    fn on_leave(&mut self, _node: NodeId) {
+        self.depth -= 1;
+    }
+
+    fn on_complete(&mut self, path: &Path, _hops: usize, _cost: u32) -> ControlFlow<()> {
+        self.paths.push(path.clone());
+        self.stats.paths_found += 1;
+        ControlFlow::Continue(())
+    }
+}
+
+// Visitor behind `find_paths_detailed`.
+pub(super) struct DetailedPathCollector<'a> {
+    pub(super) results: &'a mut Vec<PathResult>,
+}
+
+impl PathVisitor for DetailedPathCollector<'_> {
+    fn on_complete(&mut self, path: &Path, hops: usize, cost: u32) -> ControlFlow<()> {
+        self.results.push(PathResult {
+            path: path.clone(),
+            hops,
+            cost,
+        });
+        ControlFlow::Continue(())
+    }
+}
+
+impl Topology {
+    /// Runs the `find_paths` search, in the same order, reporting to
+    /// `visitor` instead of collecting paths. Bad endpoints are reported as
+    /// in `find_paths`; finding nothing is not an error.
+    pub fn walk_paths<V: PathVisitor>(&self,
+                                      start_id: NodeId,
+                                      start_if_id: IfaceIndex,
+                                      finish_id: NodeId,
+                                      finish_if_id: IfaceIndex,
+                                      visitor: &mut V) -> Result<(), PathError> {
+        self.check_endpoints(start_id, start_if_id, finish_id, finish_if_id)?;
+
+        if start_id != finish_id || start_if_id != finish_if_id {
+            let _ = self.find_path_rec(start_id, start_if_id, finish_id, finish_if_id,
+                                       &mut Path::new(), &mut HashSet::new(), 0, &|_| true, visitor);
+        }
+        Ok(())
+    }
+
+    /// `find_paths` for long searches: `progress` is called with the number
+    /// of paths found so far after every `every` paths (an `every` of 0
+    /// counts as 1). The paths are the same, in the same order.
+    pub fn find_paths_with_progress<F: FnMut(usize)>(&self,
+                                                     start_id: NodeId,
+                                                     start_if_id: IfaceIndex,
+                                                     finish_id: NodeId,
+                                                     finish_if_id: IfaceIndex,
+                                                     every: usize,
+                                                     progress: F) -> Result<Vec<Path>, PathError> {
+        let mut paths: Vec<Path> = Vec::new();
+        let mut visitor = ProgressCollector { paths: &mut paths, every: every.max(1), progress };
+        self.walk_paths(start_id, start_if_id, finish_id, finish_if_id, &mut visitor)?;
+        non_empty(paths)
+    }
+
+    /// `find_paths` that also reports how much searching it took, e.g. to
+    /// see why a search was slow or to pick a hop bound. The paths and the
+    /// errors are the same as from `find_paths`. The stats come back either
+    /// way, since a search that finds nothing is often the one worth
+    /// looking into; they are all zero if the endpoints are bad.
+    pub fn find_paths_with_stats(&self,
+                                 start_id: NodeId,
+                                 start_if_id: IfaceIndex,
+                                 finish_id: NodeId,
+                                 finish_if_id: IfaceIndex) -> (Result<Vec<Path>, PathError>, SearchStats) {
+        let mut paths: Vec<Path> = Vec::new();
+        let mut visitor = StatsCollector { paths: &mut paths, stats: SearchStats::default(), depth: 0 };
+        let res = self.walk_paths(start_id, start_if_id, finish_id, finish_if_id, &mut visitor);
+        let stats = visitor.stats;
+        (res.and_then(|()| non_empty(paths)), stats)
+    }
+}
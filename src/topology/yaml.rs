@@ -0,0 +1,186 @@
+use alloc::string::String;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use alloc::vec;
+use core::fmt::Display;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use super::IfaceIndex;
+use super::Interface;
+use super::InterfaceType;
+use super::NodeId;
+use super::Topology;
+use super::TopologyError;
+use super::TopologyNode;
+use super::collections::BTreeSet;
+
+// The document `from_yaml` reads and `to_yaml` writes:
+//
+//     nodes: [10, 11]
+//     links:
+//       - {a: 10, a_if: 1, b: 11, b_if: 1}
+//     gateways:
+//       - {node: 11, iface: 2}
+//     apps:
+//       - {node: 10, iface: 0}
+//     segments:
+//       - [{node: 10, iface: 3}, {node: 11, iface: 3}, {node: 12, iface: 3}]
+#[derive(Debug, Serialize, Deserialize)]
+struct Document {
+    nodes: Vec<u32>,
+    #[serde(default)]
+    links: Vec<Link>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    segments: Vec<Vec<Endpoint>>,
+    #[serde(default)]
+    gateways: Vec<Endpoint>,
+    #[serde(default)]
+    apps: Vec<Endpoint>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Link {
+    a: u32,
+    a_if: u8,
+    b: u32,
+    b_if: u8,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Endpoint {
+    node: u32,
+    iface: u8,
+}
+
+#[derive(Debug)]
+pub enum YamlError {
+    /// The text isn't YAML or doesn't follow the schema.
+    Parse(serde_yaml::Error),
+    /// The document parsed but describes a broken topology.
+    Invalid(Vec<TopologyError>),
+}
+
+impl Display for YamlError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            YamlError::Parse(err) => f.write_fmt(format_args!("invalid topology YAML: {}", err)),
+            YamlError::Invalid(errors) => f.write_fmt(format_args!("invalid topology: {}",
+                                                                   errors.iter()
+                                                                         .map(|x| x.to_string())
+                                                                         .collect::<Vec<String>>()
+                                                                         .join("; "))),
+        }
+    }
+}
+
+impl core::error::Error for YamlError {}
+
+impl Topology {
+    /// Reads a topology from YAML with a `nodes:` list of ids and optional
+    /// `links:` (`{a, a_if, b, b_if}`), `gateways:` and `apps:`
+    /// (`{node, iface}`) lists. Links may share an interface. Each entry of
+    /// the optional `segments:` list holds the `{node, iface}` members of one
+    /// shared segment, set up as by `add_segment`. Like
+    /// `TopologyBuilder::build`, every problem is reported at once.
+    pub fn from_yaml(s: &str) -> Result<Topology, YamlError> {
+        let doc: Document = serde_yaml::from_str(s).map_err(YamlError::Parse)?;
+        let mut errors: Vec<TopologyError> = Vec::new();
+        let mut topo = Topology::new();
+
+        for id in doc.nodes.into_iter().map(NodeId) {
+            topo.nodes.entry(id).or_insert_with(|| TopologyNode::new(id));
+        }
+
+        for link in doc.links {
+            let (a_id, a_if_id, b_id, b_if_id) = (NodeId(link.a), IfaceIndex(link.a_if), NodeId(link.b), IfaceIndex(link.b_if));
+            let res = topo.add_neighbor(a_id, a_if_id, b_id, b_if_id);
+            if let Err(err) = res.and_then(|()| topo.add_neighbor(b_id, b_if_id, a_id, a_if_id)) {
+                errors.push(err);
+            }
+        }
+
+        for segment in doc.segments {
+            let members: Vec<(NodeId, IfaceIndex)> = segment.into_iter()
+                                                            .map(|x| (NodeId(x.node), IfaceIndex(x.iface)))
+                                                            .collect();
+            if let Err(err) = topo.add_segment(&members) {
+                errors.push(err);
+            }
+        }
+
+        for (endpoints, if_type) in [(doc.gateways, InterfaceType::Internet), (doc.apps, InterfaceType::LocalApp)] {
+            for endpoint in endpoints {
+                let (id, if_id) = (NodeId(endpoint.node), IfaceIndex(endpoint.iface));
+                let res = match topo.nodes.get_mut(&id) {
+                    Some(node) => node.try_add_iface(Interface::new(if_id, if_type, vec![])),
+                    None => Err(TopologyError::NodeNotFound(id)),
+                };
+                if let Err(err) = res {
+                    errors.push(err);
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(topo)
+        } else {
+            Err(YamlError::Invalid(errors))
+        }
+    }
+
+    /// Writes the topology in the `from_yaml` format, every list sorted.
+    /// `SharedMedium` interfaces go to `segments:`, one entry per segment.
+    /// Only the graph is kept: costs, bandwidths, labels and link state are
+    /// dropped, as are `LocalNet` interfaces without links.
+    pub fn to_yaml(&self) -> String {
+        let mut nodes: Vec<u32> = self.nodes.keys().map(|id| id.0).collect();
+        nodes.sort();
+
+        let links: Vec<Link> = self.canonical_links()
+                                   .into_iter()
+                                   .filter(|&(a_id, a_if_id, _, _)| self.nodes[&a_id].ifaces[&a_if_id].if_type == InterfaceType::LocalNet)
+                                   .map(|(a_id, a_if_id, b_id, b_if_id)| Link { a: a_id.0, a_if: a_if_id.0, b: b_id.0, b_if: b_if_id.0 })
+                                   .collect();
+
+        let segments: BTreeSet<Vec<(NodeId, IfaceIndex)>> = self.nodes
+                                                                .values()
+                                                                .flat_map(|node| node.ifaces.values().map(move |iface| (node.id, iface)))
+                                                                .filter(|(_, iface)| iface.if_type == InterfaceType::SharedMedium)
+                                                                .map(|(id, iface)| {
+                                                                    let mut members = iface.neighbors.clone();
+                                                                    members.push((id, iface.id));
+                                                                    members.sort();
+                                                                    members
+                                                                })
+                                                                .collect();
+        let segments: Vec<Vec<Endpoint>> = segments.into_iter()
+                                                   .map(|members| members.into_iter()
+                                                                         .map(|(id, if_id)| Endpoint { node: id.0, iface: if_id.0 })
+                                                                         .collect())
+                                                   .collect();
+
+        let endpoints = |if_type: InterfaceType| {
+            let mut res: Vec<(NodeId, IfaceIndex)> = self.nodes
+                                                         .values()
+                                                         .flat_map(|node| node.ifaces.values().map(move |iface| (node.id, iface)))
+                                                         .filter(|(_, iface)| iface.if_type == if_type)
+                                                         .map(|(id, iface)| (id, iface.id))
+                                                         .collect();
+            res.sort();
+            res.into_iter()
+               .map(|(id, if_id)| Endpoint { node: id.0, iface: if_id.0 })
+               .collect::<Vec<Endpoint>>()
+        };
+
+        let doc = Document {
+            nodes,
+            links,
+            segments,
+            gateways: endpoints(InterfaceType::Internet),
+            apps: endpoints(InterfaceType::LocalApp),
+        };
+        serde_yaml::to_string(&doc).expect("topology documents always serialize")
+    }
+}